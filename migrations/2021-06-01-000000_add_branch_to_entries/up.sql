@@ -0,0 +1 @@
+ALTER TABLE entries ADD COLUMN branch TEXT NOT NULL DEFAULT 'master';
@@ -0,0 +1,20 @@
+CREATE TABLE tasks (
+    id integer PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    path TEXT NOT NULL,
+    file_kind TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    sha TEXT NOT NULL,
+    blob TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    last_author TEXT NOT NULL,
+    last_changed TIMESTAMP NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created TIMESTAMP NOT NULL,
+    started TIMESTAMP,
+    completed TIMESTAMP
+);
+CREATE INDEX tasks_status_index ON tasks (status);
+CREATE INDEX tasks_name_status_index ON tasks (name, status);
@@ -0,0 +1,23 @@
+CREATE TABLE tasks_backup(
+    id integer PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    path TEXT NOT NULL,
+    file_kind TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    sha TEXT NOT NULL,
+    blob TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    last_author TEXT NOT NULL,
+    last_changed TIMESTAMP NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created TIMESTAMP NOT NULL,
+    started TIMESTAMP,
+    completed TIMESTAMP
+);
+INSERT INTO tasks_backup SELECT id, name, branch, path, file_kind, revision, sha, blob, size, last_author, last_changed, status, error, created, started, completed FROM tasks;
+DROP TABLE tasks;
+ALTER TABLE tasks_backup RENAME TO tasks;
+CREATE INDEX tasks_status_index ON tasks (status);
+CREATE INDEX tasks_name_status_index ON tasks (name, status);
@@ -0,0 +1 @@
+DROP INDEX entries_name_file_kind_path_created_index;
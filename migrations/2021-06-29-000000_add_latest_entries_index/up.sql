@@ -0,0 +1 @@
+CREATE INDEX entries_name_file_kind_path_created_index ON entries (name, file_kind, path, created);
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use diesel::{
+    prelude::*,
+    sql_query,
+    sql_types::{Nullable, Text},
+    sqlite::SqliteConnection,
+};
+use serde_derive::Serialize;
+use slog::{error, Logger};
+
+use crate::{
+    models::{Entry, FileKind, FileKindMapping, StatKind, StatKindMapping},
+    util::{MODULE_RE, PAIR_RE},
+    worker::{Package, Worker},
+};
+
+#[derive(Serialize)]
+pub enum PackageKind {
+    Module,
+    Pair,
+}
+
+#[derive(Serialize)]
+pub struct Total {
+    pub stat_kind: StatKind,
+    pub value: i64,
+}
+
+#[derive(Serialize)]
+pub struct PackageStats {
+    pub name: String,
+    pub kind: Option<PackageKind>,
+    pub branch: String,
+    pub stats: Vec<Entry>,
+    pub totals: Vec<Total>,
+}
+
+#[derive(Serialize)]
+pub struct AggregateTotals {
+    pub modules: Vec<Total>,
+    pub pairs: Vec<Total>,
+}
+
+pub struct AggregateFilters<'a> {
+    pub topic: Option<&'a str>,
+    pub file_kind: Option<&'a FileKind>,
+    pub stat_kind: Option<&'a StatKind>,
+}
+
+fn get_latest_entries(
+    conn: &SqliteConnection,
+    name: &str,
+    branch: &str,
+    filters: &AggregateFilters,
+) -> QueryResult<Vec<Entry>> {
+    // Diesel doesn't support self JOINs or GROUP BY :(
+    sql_query(
+        "
+            SELECT *
+            FROM entries e1
+            JOIN (
+                SELECT id, MAX(created)
+                FROM entries e3
+                WHERE name = ? AND branch = ?
+                    AND file_kind = COALESCE(?, file_kind) AND stat_kind = COALESCE(?, stat_kind)
+                    AND id = (
+                        SELECT id
+                        FROM entries
+                        WHERE name = e3.name AND file_kind = e3.file_kind AND path = e3.path
+                            AND branch = e3.branch AND stat_kind = e3.stat_kind
+                        ORDER BY last_changed DESC, revision DESC, created DESC
+                        LIMIT 1
+                    )
+                GROUP BY stat_kind, path
+            ) e2
+            ON e1.id = e2.id
+        ",
+    )
+    .bind::<Text, _>(name)
+    .bind::<Text, _>(branch)
+    .bind::<Nullable<FileKindMapping>, _>(filters.file_kind)
+    .bind::<Nullable<StatKindMapping>, _>(filters.stat_kind)
+    .load(conn)
+}
+
+fn sum_totals<'a>(entries: impl Iterator<Item = &'a Entry>) -> Vec<Total> {
+    let mut totals: HashMap<StatKind, i64> = HashMap::new();
+    for entry in entries {
+        if let Some(value) = entry.value.0.as_i64() {
+            *totals.entry(entry.stat_kind.clone()).or_insert(0) += value;
+        }
+    }
+
+    let mut totals = totals
+        .into_iter()
+        .map(|(stat_kind, value)| Total { stat_kind, value })
+        .collect::<Vec<_>>();
+    totals.sort_by_key(|total| format!("{:?}", total.stat_kind));
+    totals
+}
+
+pub async fn get_aggregate_stats(
+    worker: &Worker,
+    conn: &SqliteConnection,
+    logger: &Logger,
+    packages: &[Package],
+    filters: &AggregateFilters,
+) -> QueryResult<(Vec<PackageStats>, AggregateTotals)> {
+    let mut package_stats = Vec::new();
+    for package in packages.iter().filter(|package| {
+        filters
+            .topic
+            .map_or(true, |topic| package.topics.iter().any(|t| t == topic))
+    }) {
+        let branch = match worker.get_branch(&package.name, None).await {
+            Ok(branch) => branch,
+            Err(err) => {
+                error!(logger, "Error getting default branch: {}", err; "package" => package.name.clone());
+                continue;
+            },
+        };
+        let stats = get_latest_entries(conn, &package.name, &branch, filters)?;
+        if stats.is_empty() {
+            continue;
+        }
+
+        package_stats.push(PackageStats {
+            kind: if MODULE_RE.is_match(&package.name) {
+                Some(PackageKind::Module)
+            } else if PAIR_RE.is_match(&package.name) {
+                Some(PackageKind::Pair)
+            } else {
+                None
+            },
+            name: package.name.clone(),
+            branch,
+            totals: sum_totals(stats.iter()),
+            stats,
+        });
+    }
+
+    let totals = AggregateTotals {
+        modules: sum_totals(
+            package_stats
+                .iter()
+                .filter(|package| matches!(package.kind, Some(PackageKind::Module)))
+                .flat_map(|package| package.stats.iter()),
+        ),
+        pairs: sum_totals(
+            package_stats
+                .iter()
+                .filter(|package| matches!(package.kind, Some(PackageKind::Pair)))
+                .flat_map(|package| package.stats.iter()),
+        ),
+    };
+
+    Ok((package_stats, totals))
+}
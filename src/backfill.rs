@@ -0,0 +1,30 @@
+use chrono::Datelike;
+
+use crate::worker::Commit;
+
+pub enum BackfillInterval {
+    Commits(usize),
+    Monthly,
+}
+
+// Commits are expected newest first, so the latest commit of each month is selected
+pub fn select_snapshots<'a>(commits: &'a [Commit], interval: &BackfillInterval) -> Vec<&'a Commit> {
+    match interval {
+        BackfillInterval::Commits(every) => commits.iter().step_by(*every).collect(),
+        BackfillInterval::Monthly => {
+            let mut last_month = None;
+            commits
+                .iter()
+                .filter(|commit| {
+                    let month = Some((commit.committed.year(), commit.committed.month()));
+                    if month == last_month {
+                        false
+                    } else {
+                        last_month = month;
+                        true
+                    }
+                })
+                .collect()
+        },
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::{prelude::*, sqlite::SqliteConnection};
+use futures::{stream, StreamExt};
+use serde_derive::Serialize;
+use slog::{error, Logger};
+
+use crate::{
+    models::{FileKind, StatKind},
+    util::JsonValue,
+    worker::{File, Worker},
+    MAX_CONCURRENT_TASKS,
+};
+
+type Stats = HashMap<(String, String, FileKind), Vec<(StatKind, JsonValue)>>;
+type Values = HashMap<(String, FileKind, StatKind), JsonValue>;
+
+#[derive(Serialize)]
+pub struct Comparison {
+    pub path: String,
+    pub file_kind: FileKind,
+    pub stat_kind: StatKind,
+    pub from: Option<JsonValue>,
+    pub to: Option<JsonValue>,
+    pub delta: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct Total {
+    pub stat_kind: StatKind,
+    pub from: i64,
+    pub to: i64,
+    pub delta: i64,
+}
+
+// Revisions off the branch would replace its latest statistics if recorded, so missing ones are only kept in memory
+async fn get_stats(
+    worker: &Worker,
+    conn: &SqliteConnection,
+    logger: &Logger,
+    name: &str,
+    files: &[&(File, FileKind)],
+) -> QueryResult<Stats> {
+    let mut stats = HashMap::new();
+    let mut uncomputed_files = Vec::new();
+    for (file, file_kind) in files.iter().copied() {
+        let key = (file.path.clone(), file.sha.clone(), file_kind.clone());
+        if stats.contains_key(&key) {
+            continue;
+        }
+
+        let existing_entries = Worker::get_existing_entries(conn, name, file, file_kind)?;
+        if existing_entries.is_empty() {
+            uncomputed_files.push((file, file_kind));
+        }
+        stats.insert(
+            key,
+            existing_entries
+                .into_iter()
+                .map(|entry| (entry.stat_kind, entry.value))
+                .collect(),
+        );
+    }
+
+    let results = stream::iter(
+        uncomputed_files
+            .iter()
+            .map(|(file, file_kind)| worker.compute_file_stats(logger, name, file, file_kind)),
+    )
+    .buffered(MAX_CONCURRENT_TASKS)
+    .collect::<Vec<_>>()
+    .await;
+    for ((file, file_kind), result) in uncomputed_files.into_iter().zip(results) {
+        match result {
+            Ok(file_stats) => {
+                stats.insert(
+                    (file.path.clone(), file.sha.clone(), file_kind.clone()),
+                    file_stats
+                        .into_iter()
+                        .map(|(stat_kind, value)| (stat_kind, value.into()))
+                        .collect(),
+                );
+            },
+            Err(err) => error!(logger, "Error computing compared file: {:?}", err; "path" => file.path.clone()),
+        }
+    }
+
+    Ok(stats)
+}
+
+fn get_values(stats: &Stats, files: &[(File, FileKind)]) -> Values {
+    let mut values = HashMap::new();
+    for (file, file_kind) in files {
+        if let Some(file_stats) = stats.get(&(file.path.clone(), file.sha.clone(), file_kind.clone())) {
+            for (stat_kind, value) in file_stats {
+                values.insert((file.path.clone(), file_kind.clone(), stat_kind.clone()), value.clone());
+            }
+        }
+    }
+
+    values
+}
+
+// Files missing from a revision count as zero, files whose statistics are missing or not numeric can't be compared
+fn get_count(values: &Values, files: &[(File, FileKind)], key: &(String, FileKind, StatKind)) -> Option<i64> {
+    let (path, file_kind, _) = key;
+    match values.get(key) {
+        Some(value) => value.0.as_i64(),
+        None if !files.iter().any(|(file, kind)| &file.path == path && kind == file_kind) => Some(0),
+        None => None,
+    }
+}
+
+pub async fn compare(
+    worker: &Worker,
+    conn: &SqliteConnection,
+    logger: &Logger,
+    name: &str,
+    from_files: &[(File, FileKind)],
+    to_files: &[(File, FileKind)],
+) -> QueryResult<(Vec<Comparison>, Vec<Total>)> {
+    let files = from_files.iter().chain(to_files.iter()).collect::<Vec<_>>();
+    let stats = get_stats(worker, conn, logger, name, &files).await?;
+    let from_values = get_values(&stats, from_files);
+    let to_values = get_values(&stats, to_files);
+
+    let mut keys = from_values
+        .keys()
+        .chain(to_values.keys())
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    keys.sort_by_key(|(path, file_kind, stat_kind)| (path.clone(), file_kind.to_string(), format!("{:?}", stat_kind)));
+
+    let mut totals: HashMap<StatKind, (i64, i64)> = HashMap::new();
+    let comparisons = keys
+        .into_iter()
+        .map(|key| {
+            let delta = match (
+                get_count(&from_values, from_files, &key),
+                get_count(&to_values, to_files, &key),
+            ) {
+                (Some(from_count), Some(to_count)) => {
+                    let total = totals.entry(key.2.clone()).or_insert((0, 0));
+                    total.0 += from_count;
+                    total.1 += to_count;
+                    Some(to_count - from_count)
+                },
+                _ => None,
+            };
+
+            Comparison {
+                from: from_values.get(&key).cloned(),
+                to: to_values.get(&key).cloned(),
+                delta,
+                path: key.0,
+                file_kind: key.1,
+                stat_kind: key.2,
+            }
+        })
+        .collect();
+
+    let mut totals = totals
+        .into_iter()
+        .map(|(stat_kind, (from, to))| Total {
+            stat_kind,
+            from,
+            to,
+            delta: to - from,
+        })
+        .collect::<Vec<_>>();
+    totals.sort_by_key(|total| format!("{:?}", total.stat_kind));
+
+    Ok((comparisons, totals))
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, sqlite::SqliteConnection};
+use serde_derive::Serialize;
+
+use crate::{
+    models::{Entry, FileKind, StatKind},
+    schema::entries,
+    util::JsonValue,
+};
+
+#[derive(Default)]
+pub struct HistoryFilters<'a> {
+    pub file_kind: Option<&'a FileKind>,
+    pub stat_kind: Option<&'a StatKind>,
+    pub path: Option<&'a str>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct Point {
+    pub sha: String,
+    pub revision: i32,
+    pub last_changed: NaiveDateTime,
+    pub last_author: String,
+    pub created: NaiveDateTime,
+    pub value: JsonValue,
+}
+
+#[derive(Serialize)]
+pub struct Series {
+    pub path: String,
+    pub file_kind: FileKind,
+    pub stat_kind: StatKind,
+    pub values: Vec<Point>,
+}
+
+pub fn get_history(
+    conn: &SqliteConnection,
+    name: &str,
+    branch: &str,
+    filters: &HistoryFilters,
+) -> QueryResult<Vec<Series>> {
+    let mut query = entries::table
+        .filter(entries::name.eq(name))
+        .filter(entries::branch.eq(branch))
+        .into_boxed();
+    if let Some(file_kind) = filters.file_kind {
+        query = query.filter(entries::file_kind.eq(file_kind));
+    }
+    if let Some(stat_kind) = filters.stat_kind {
+        query = query.filter(entries::stat_kind.eq(stat_kind));
+    }
+    if let Some(path) = filters.path {
+        query = query.filter(entries::path.eq(path));
+    }
+    if let Some(since) = filters.since {
+        query = query.filter(entries::last_changed.ge(since));
+    }
+    if let Some(until) = filters.until {
+        query = query.filter(entries::last_changed.le(until));
+    }
+
+    let entries = query
+        .order((
+            entries::path,
+            entries::file_kind,
+            entries::stat_kind,
+            entries::created.desc(),
+        ))
+        .load::<Entry>(conn)?;
+
+    let mut history: Vec<Series> = Vec::new();
+    let mut seen_shas = HashSet::new();
+    for entry in entries {
+        let same_series = history.last().map_or(false, |series| {
+            series.path == entry.path && series.file_kind == entry.file_kind && series.stat_kind == entry.stat_kind
+        });
+        if !same_series {
+            seen_shas.clear();
+            history.push(Series {
+                path: entry.path.clone(),
+                file_kind: entry.file_kind.clone(),
+                stat_kind: entry.stat_kind.clone(),
+                values: Vec::new(),
+            });
+        }
+
+        // Entries are ordered newest first, so only the latest computation for each SHA is kept
+        if seen_shas.insert(entry.sha.clone()) {
+            history.last_mut().unwrap().values.push(Point {
+                sha: entry.sha,
+                revision: entry.revision,
+                last_changed: entry.last_changed,
+                last_author: entry.last_author,
+                created: entry.created,
+                value: entry.value,
+            });
+        }
+    }
+
+    for series in &mut history {
+        series.values.sort_by_key(|point| (point.last_changed, point.revision));
+    }
+
+    Ok(history)
+}
@@ -2,9 +2,14 @@
 #![deny(clippy::all)]
 #![allow(proc_macro_derive_resolution_fallback)]
 
+mod aggregate;
+mod backfill;
+mod compare;
 mod db;
+mod history;
 mod models;
 mod schema;
+mod sources;
 mod stats;
 mod util;
 mod worker;
@@ -16,12 +21,15 @@ mod tests;
 #[macro_use]
 extern crate diesel;
 
-use std::{cmp::max, collections::HashSet, env, hash::BuildHasher, sync::Arc, thread, time::Duration};
+use std::{cmp::max, collections::HashSet, env, hash::BuildHasher, path::PathBuf, sync::Arc, thread, time::Duration};
 
-use chrono::Utc;
-use diesel::{prelude::*, sql_query, sql_types::Text};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, sql_query, sql_types::Text, sqlite::SqliteConnection};
 use dotenv::dotenv;
-use futures::{future::join_all, FutureExt};
+use futures::{
+    future::{join_all, Future},
+    stream, FutureExt, StreamExt,
+};
 use lazy_static::lazy_static;
 use rocket::{
     get,
@@ -36,17 +44,26 @@ use rocket_cors::{AllowedHeaders, AllowedOrigins};
 use slog::{debug, error, o, Drain, Logger};
 use tokio::runtime::{self, Runtime};
 
+use aggregate::AggregateFilters;
+use backfill::BackfillInterval;
 use db::DbConn;
-use models::{FileKind, FileKindMapping, NewEntry};
+use history::HistoryFilters;
+use models::{FileKind, FileKindMapping, NewEntry, StatKind, TaskStatus, TaskStatusMapping};
 use schema::entries as entries_db;
-use util::{normalize_name, JsonResult, Params};
-use worker::{Package, Task, Worker};
+use sources::{GitHubProvider, GitProvider, LocalProvider, SourceProvider};
+use stats::StatsResults;
+use util::{
+    normalize_name, parse_datetime, Admin, AdminAuthToken, AggregateParams, BackfillParams, CompareParams,
+    HistoryParams, JsonResult, Params,
+};
+use worker::{Failure, Package, Task, Worker};
 
 pub const ORGANIZATION_ROOT: &str = "https://github.com/apertium";
-pub const ORGANIZATION_RAW_ROOT: &str = "https://raw.githubusercontent.com/apertium";
 pub const GITHUB_GRAPHQL_API_ENDPOINT: &str = "https://api.github.com/graphql";
 pub const PACKAGE_UPDATE_MIN_INTERVAL: Duration = Duration::from_secs(10);
 pub const PACKAGE_UPDATE_FALLBACK_INTERVAL: Duration = Duration::from_secs(120);
+pub const MAX_CONCURRENT_TASKS: usize = 16;
+pub const TASK_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
 
 lazy_static! {
     pub static ref RUNTIME: Runtime = runtime::Runtime::new().unwrap();
@@ -56,15 +73,34 @@ lazy_static! {
         .unwrap();
 }
 
+fn spawn_tasks<F>(worker: Arc<Worker>, name: String, futures: Vec<F>)
+where
+    F: Future<Output = (Task, StatsResults)> + Send + 'static,
+{
+    RUNTIME.spawn(async move {
+        stream::iter(futures)
+            .buffer_unordered(MAX_CONCURRENT_TASKS)
+            .map(|results| worker.handle_task_completion(&name, &results))
+            .collect::<Vec<_>>()
+            .await
+    });
+}
+
 fn launch_tasks_and_reply(
     worker: &State<Arc<Worker>>,
     name: String,
     kind: Option<&FileKind>,
     options: Params,
 ) -> JsonResult {
-    match RUNTIME.block_on(worker.build_tasks(&name, kind, options.is_recursive())) {
-        Ok((ref new_tasks, ref in_progress_tasks, ref _future))
-            if new_tasks.is_empty() && in_progress_tasks.is_empty() =>
+    match RUNTIME.block_on(worker.build_tasks(
+        &name,
+        kind,
+        options.branch.as_deref(),
+        options.is_recursive(),
+        options.is_force(),
+    )) {
+        Ok((ref new_tasks, ref in_progress_tasks, ref reused_entries, ref _future))
+            if new_tasks.is_empty() && in_progress_tasks.is_empty() && reused_entries.is_empty() =>
         {
             JsonResult::Err(
                 Some(json!({
@@ -74,16 +110,19 @@ fn launch_tasks_and_reply(
                 Status::NotFound,
             )
         },
-        Ok((_new_tasks, in_progress_tasks, futures)) => {
+        Ok((ref new_tasks, ref in_progress_tasks, ref reused_entries, ref _future))
+            if new_tasks.is_empty() && in_progress_tasks.is_empty() =>
+        {
+            JsonResult::Ok(json!({
+                "name": name,
+                "stats": reused_entries,
+                "errors": vec![] as Vec<Failure>,
+                "in_progress": vec![] as Vec<Task>,
+            }))
+        },
+        Ok((_new_tasks, in_progress_tasks, reused_entries, futures)) => {
             if options.is_async() {
-                let future_name = name.clone();
-                let future_worker = (*worker).clone();
-                RUNTIME.spawn(async move {
-                    join_all(futures.into_iter().map(|future| {
-                        future.map(|results| future_worker.handle_task_completion(&future_name, &results))
-                    }))
-                    .await
-                });
+                spawn_tasks((*worker).clone(), name.clone(), futures);
 
                 JsonResult::Err(
                     Some(json!({
@@ -97,10 +136,18 @@ fn launch_tasks_and_reply(
                     .into_iter()
                     .map(|future| future.map(|results| worker.handle_task_completion(&name, &results)));
                 let result = RUNTIME.block_on(join_all(futures));
-                let stats: Vec<&NewEntry> = result.iter().flatten().collect();
+                let mut stats: Vec<&NewEntry> = reused_entries.iter().collect();
+                let mut errors: Vec<&Failure> = Vec::new();
+                for outcome in &result {
+                    match outcome {
+                        Ok(new_entries) => stats.extend(new_entries),
+                        Err(failure) => errors.push(failure),
+                    }
+                }
                 JsonResult::Ok(json!({
                     "name": name,
                     "stats": stats,
+                    "errors": errors,
                     "in_progress": vec![] as Vec<Task>,
                 }))
             }
@@ -142,6 +189,30 @@ fn parse_kind_param(name: &str, kind: &str) -> Result<FileKind, (Option<JsonValu
     })
 }
 
+fn parse_stat_kind_param(name: &str, kind: &str) -> Result<StatKind, (Option<JsonValue>, Status)> {
+    StatKind::from_string(kind).map_err(|err| {
+        (
+            Some(json!({
+                "name": name,
+                "error": err,
+            })),
+            Status::BadRequest,
+        )
+    })
+}
+
+fn parse_datetime_param(name: &str, value: &str) -> Result<NaiveDateTime, (Option<JsonValue>, Status)> {
+    parse_datetime(value).map_err(|err| {
+        (
+            Some(json!({
+                "name": name,
+                "error": err,
+            })),
+            Status::BadRequest,
+        )
+    })
+}
+
 fn handle_db_error(logger: &Logger, err: diesel::result::Error) -> (Option<JsonValue>, Status) {
     error!(logger, "Encountered database level error: {:?}", err);
     (None, Status::InternalServerError)
@@ -185,6 +256,72 @@ fn get_package_names(worker: &State<Arc<Worker>>) -> HashSet<String> {
         .collect()
 }
 
+fn get_failures(
+    conn: &SqliteConnection,
+    name: &str,
+    branch: &str,
+    maybe_kind: Option<&FileKind>,
+) -> QueryResult<Vec<Failure>> {
+    // Only the latest outcome of each file matters, a failure followed by a success is resolved
+    let tasks: Vec<models::Task> = match maybe_kind {
+        Some(file_kind) => sql_query(
+            "
+                SELECT *
+                FROM tasks t1
+                JOIN (
+                    SELECT id, MAX(completed)
+                    FROM tasks
+                    WHERE name = ? AND branch = ? AND file_kind = ? AND completed IS NOT NULL
+                    GROUP BY file_kind, path
+                ) t2
+                ON t1.id = t2.id
+                WHERE t1.status = ?
+            ",
+        )
+        .bind::<Text, _>(name)
+        .bind::<Text, _>(branch)
+        .bind::<FileKindMapping, _>(file_kind)
+        .bind::<TaskStatusMapping, _>(TaskStatus::Failed)
+        .load(conn)?,
+        None => sql_query(
+            "
+                SELECT *
+                FROM tasks t1
+                JOIN (
+                    SELECT id, MAX(completed)
+                    FROM tasks
+                    WHERE name = ? AND branch = ? AND completed IS NOT NULL
+                    GROUP BY file_kind, path
+                ) t2
+                ON t1.id = t2.id
+                WHERE t1.status = ?
+            ",
+        )
+        .bind::<Text, _>(name)
+        .bind::<Text, _>(branch)
+        .bind::<TaskStatusMapping, _>(TaskStatus::Failed)
+        .load(conn)?,
+    };
+
+    Ok(tasks.into_iter().map(Failure::from).collect())
+}
+
+fn get_branch(
+    worker: &State<Arc<Worker>>,
+    name: &str,
+    maybe_branch: Option<&str>,
+) -> Result<String, (Option<JsonValue>, Status)> {
+    RUNTIME.block_on(worker.get_branch(name, maybe_branch)).map_err(|err| {
+        (
+            Some(json!({
+                "name": name,
+                "error": err,
+            })),
+            Status::BadRequest,
+        )
+    })
+}
+
 #[get("/")]
 fn index(accept: Option<&Accept>) -> Content<&str> {
     if accept.map_or(false, |a| a.preferred().media_type() == &MediaType::HTML) {
@@ -197,15 +334,36 @@ fn index(accept: Option<&Accept>) -> Content<&str> {
 GET /apertium-<code1>(-<code2>)
 retrieves statistics for the specified package
 
+GET /apertium-<code1>(-<code2>)?branch=<branch>
+retrieves statistics for the specified package on a non-default branch
+
 GET /apertium-<code1>(-<code2>)/<kind>
 retrieves <kind> statistics for the specified package
 
+GET /apertium-<code1>(-<code2>)/errors
+retrieves errors encountered while calculating statistics for the specified package
+
+GET /apertium-<code1>(-<code2>)/compare?from=<revision>&to=<revision>
+compares statistics for the specified package between two revisions
+
+GET /apertium-<code1>(-<code2>)(/<kind>)/history?since=<date>&until=<date>&stat_kind=<kind>&path=<path>
+retrieves the history of (<kind>) statistics for the specified package, one value per file revision
+
 POST /apertium-<code1>(-<code2>)
-calculates statistics for the specified package
+calculates statistics for the specified package, reusing those of unchanged files
+
+POST /apertium-<code1>(-<code2>)?force=true
+recalculates statistics for all files of the specified package
 
 POST /apertium-<code1>(-<code2>)/<kind>
 calculates <kind> statistics for the specified package
 
+POST /apertium-<code1>(-<code2>)/backfill?every=<n>|monthly=true
+calculates statistics for every <n>th commit or, by default, the last commit of each month (requires admin token)
+
+GET /stats?topic=<topic>&kind=<kind>&stat_kind=<kind>
+retrieves the latest statistics of all packages with totals for modules and pairs
+
 GET /packages/<?query>
 lists packages with names including the optional query
 
@@ -228,15 +386,19 @@ fn openapi_yaml() -> Content<&'static str> {
 #[get("/<name>?<params..>", rank = 1)]
 fn get_stats(name: String, params: Form<Option<Params>>, conn: DbConn, worker: State<Arc<Worker>>) -> JsonResult {
     let name = parse_name_param(&name, get_package_names(&worker))?;
+    let params = params.into_inner().unwrap_or_default();
+    let branch = get_branch(&worker, &name, params.branch.as_deref())?;
 
     let entries: Vec<models::Entry> = entries_db::table
         .filter(entries_db::name.eq(&name))
+        .filter(entries_db::branch.eq(&branch))
         .order(entries_db::created)
         .limit(1)
         .load::<models::Entry>(&*conn)
         .map_err(|err| handle_db_error(&worker.logger, err))?;
+    let errors = get_failures(&conn, &name, &branch, None).map_err(|err| handle_db_error(&worker.logger, err))?;
 
-    if entries.is_empty() {
+    if entries.is_empty() && errors.is_empty() {
         if let Some(in_progress_tasks) = worker.get_tasks_in_progress(&name) {
             JsonResult::Err(
                 Some(json!({
@@ -247,30 +409,40 @@ fn get_stats(name: String, params: Form<Option<Params>>, conn: DbConn, worker: S
             )
         } else {
             drop(conn);
-            launch_tasks_and_reply(&worker, name, None, params.into_inner().unwrap_or_default())
+            launch_tasks_and_reply(&worker, name, None, params)
         }
     } else {
         // Diesel doesn't support self JOINs or GROUP BY :(
+        // Backfilled entries can be created after those of newer revisions, and revision numbers of older entries
+        // don't follow the same scale, so the latest change wins
         let entries: Vec<models::Entry> = sql_query(
             "
                 SELECT *
                 FROM entries e1
                 JOIN (
                     SELECT id, MAX(created)
-                    FROM entries
-                    WHERE name = ?
+                    FROM entries e3
+                    WHERE name = ? AND branch = ? AND id = (
+                        SELECT id
+                        FROM entries
+                        WHERE name = e3.name AND branch = e3.branch AND stat_kind = e3.stat_kind AND path = e3.path
+                        ORDER BY last_changed DESC, revision DESC, created DESC
+                        LIMIT 1
+                    )
                     GROUP BY stat_kind, path
                 ) e2
                 ON e1.id = e2.id
             ",
         )
         .bind::<Text, _>(&name)
+        .bind::<Text, _>(&branch)
         .load(&*conn)
         .map_err(|err| handle_db_error(&worker.logger, err))?;
 
         JsonResult::Ok(json!({
             "name": name,
             "stats": entries,
+            "errors": errors,
             "in_progress": worker.get_tasks_in_progress(&name).unwrap_or_else(Vec::new),
         }))
     }
@@ -286,16 +458,21 @@ fn get_specific_stats(
 ) -> JsonResult {
     let name = parse_name_param(&name, get_package_names(&worker))?;
     let file_kind = parse_kind_param(&name, &kind)?;
+    let params = params.into_inner().unwrap_or_default();
+    let branch = get_branch(&worker, &name, params.branch.as_deref())?;
 
     let entries: Vec<models::Entry> = entries_db::table
         .filter(entries_db::name.eq(&name))
+        .filter(entries_db::branch.eq(&branch))
         .filter(entries_db::file_kind.eq(&file_kind))
         .order(entries_db::created)
         .limit(1)
         .load::<models::Entry>(&*conn)
         .map_err(|err| handle_db_error(&worker.logger, err))?;
+    let errors =
+        get_failures(&conn, &name, &branch, Some(&file_kind)).map_err(|err| handle_db_error(&worker.logger, err))?;
 
-    if entries.is_empty() {
+    if entries.is_empty() && errors.is_empty() {
         if let Some(in_progress_tasks) = worker.get_tasks_in_progress(&name) {
             if in_progress_tasks.iter().filter(|task| task.kind == file_kind).count() != 0 {
                 return JsonResult::Err(
@@ -309,23 +486,32 @@ fn get_specific_stats(
         }
 
         drop(conn);
-        launch_tasks_and_reply(&worker, name, Some(&file_kind), params.into_inner().unwrap_or_default())
+        launch_tasks_and_reply(&worker, name, Some(&file_kind), params)
     } else {
         // Diesel doesn't support self JOINs or GROUP BY :(
+        // Backfilled entries can be created after those of newer revisions, and revision numbers of older entries
+        // don't follow the same scale, so the latest change wins
         let entries: Vec<models::Entry> = sql_query(
             "
                 SELECT *
                 FROM entries e1
                 JOIN (
                     SELECT id, MAX(created)
-                    FROM entries
-                    WHERE name = ? AND file_kind = ?
+                    FROM entries e3
+                    WHERE name = ? AND branch = ? AND file_kind = ? AND id = (
+                        SELECT id
+                        FROM entries
+                        WHERE name = e3.name AND branch = e3.branch AND stat_kind = e3.stat_kind AND path = e3.path
+                        ORDER BY last_changed DESC, revision DESC, created DESC
+                        LIMIT 1
+                    )
                     GROUP BY stat_kind, path
                 ) e2
                 ON e1.id = e2.id
             ",
         )
         .bind::<Text, _>(&name)
+        .bind::<Text, _>(&branch)
         .bind::<FileKindMapping, _>(&file_kind)
         .load(&*conn)
         .map_err(|err| handle_db_error(&worker.logger, err))?;
@@ -333,11 +519,165 @@ fn get_specific_stats(
         JsonResult::Ok(json!({
             "name": name,
             "stats": entries,
+            "errors": errors,
             "in_progress": worker.get_tasks_in_progress(&name).unwrap_or_else(Vec::new),
         }))
     }
 }
 
+#[get("/<name>/errors?<params..>")]
+fn get_errors(name: String, params: Form<Option<Params>>, conn: DbConn, worker: State<Arc<Worker>>) -> JsonResult {
+    let name = parse_name_param(&name, get_package_names(&worker))?;
+    let params = params.into_inner().unwrap_or_default();
+    let branch = get_branch(&worker, &name, params.branch.as_deref())?;
+
+    let errors = get_failures(&conn, &name, &branch, None).map_err(|err| handle_db_error(&worker.logger, err))?;
+    JsonResult::Ok(json!({
+        "name": name,
+        "errors": errors,
+    }))
+}
+
+fn get_history_and_reply(
+    worker: &State<Arc<Worker>>,
+    conn: &DbConn,
+    name: String,
+    file_kind: Option<&FileKind>,
+    params: HistoryParams,
+) -> JsonResult {
+    let stat_kind = match params.stat_kind {
+        Some(ref stat_kind) => Some(parse_stat_kind_param(&name, stat_kind)?),
+        None => None,
+    };
+    let since = match params.since {
+        Some(ref since) => Some(parse_datetime_param(&name, since)?),
+        None => None,
+    };
+    let until = match params.until {
+        Some(ref until) => Some(parse_datetime_param(&name, until)?),
+        None => None,
+    };
+    let branch = get_branch(worker, &name, params.branch.as_deref())?;
+
+    let filters = HistoryFilters {
+        file_kind,
+        stat_kind: stat_kind.as_ref(),
+        path: params.path.as_deref(),
+        since,
+        until,
+    };
+    let history =
+        history::get_history(conn, &name, &branch, &filters).map_err(|err| handle_db_error(&worker.logger, err))?;
+
+    JsonResult::Ok(json!({
+        "name": name,
+        "history": history,
+    }))
+}
+
+#[get("/<name>/history?<params..>")]
+fn get_history(
+    name: String,
+    params: Form<Option<HistoryParams>>,
+    conn: DbConn,
+    worker: State<Arc<Worker>>,
+) -> JsonResult {
+    let name = parse_name_param(&name, get_package_names(&worker))?;
+    get_history_and_reply(&worker, &conn, name, None, params.into_inner().unwrap_or_default())
+}
+
+#[get("/<name>/<kind>/history?<params..>")]
+fn get_specific_history(
+    name: String,
+    kind: String,
+    params: Form<Option<HistoryParams>>,
+    conn: DbConn,
+    worker: State<Arc<Worker>>,
+) -> JsonResult {
+    let name = parse_name_param(&name, get_package_names(&worker))?;
+    let file_kind = parse_kind_param(&name, &kind)?;
+    get_history_and_reply(
+        &worker,
+        &conn,
+        name,
+        Some(&file_kind),
+        params.into_inner().unwrap_or_default(),
+    )
+}
+
+#[get("/<name>/compare?<params..>")]
+fn compare_stats(
+    name: String,
+    params: Form<Option<CompareParams>>,
+    pool: State<db::Pool>,
+    worker: State<Arc<Worker>>,
+) -> JsonResult {
+    let name = parse_name_param(&name, get_package_names(&worker))?;
+    let params = params.into_inner().unwrap_or_default();
+    let file_kind = match params.kind {
+        Some(ref kind) => Some(parse_kind_param(&name, kind)?),
+        None => None,
+    };
+    let from = match params.from {
+        Some(ref from) => from.clone(),
+        None => {
+            return JsonResult::Err(
+                Some(json!({
+                    "name": name,
+                    "error": "A from revision is required",
+                })),
+                Status::BadRequest,
+            )
+        },
+    };
+    let to = match params.to {
+        Some(ref to) => to.clone(),
+        None => format!("refs/heads/{}", get_branch(&worker, &name, params.branch.as_deref())?),
+    };
+
+    let bad_request = |error: String| {
+        (
+            Some(json!({
+                "name": name,
+                "error": error,
+            })),
+            Status::BadRequest,
+        )
+    };
+    let logger = worker.logger.new(o!(
+        "package" => name.clone(),
+        "from" => from.clone(),
+        "to" => to.clone(),
+    ));
+    let list_files = |revision| {
+        RUNTIME.block_on(worker.list_revision_files(
+            &logger,
+            &name,
+            revision,
+            file_kind.as_ref(),
+            params.is_recursive(),
+        ))
+    };
+    let from_files = list_files(&from).map_err(bad_request)?;
+    let to_files = list_files(&to).map_err(bad_request)?;
+
+    let conn = pool.get().map_err(|err| {
+        error!(worker.logger, "Error getting database connection: {:?}", err);
+        (None, Status::ServiceUnavailable)
+    })?;
+    let (comparisons, totals) = RUNTIME
+        .block_on(compare::compare(&worker, &conn, &logger, &name, &from_files, &to_files))
+        .map_err(|err| handle_db_error(&worker.logger, err))?;
+
+    JsonResult::Ok(json!({
+        "name": name,
+        "from": from,
+        "to": to,
+        "stats": comparisons,
+        "totals": totals,
+    }))
+}
+
 #[post("/<name>?<params..>", rank = 1)]
 fn calculate_stats(name: String, params: Form<Option<Params>>, worker: State<Arc<Worker>>) -> JsonResult {
     let name = parse_name_param(&name, get_package_names(&worker))?;
@@ -356,6 +696,113 @@ fn calculate_specific_stats(
     launch_tasks_and_reply(&worker, name, Some(&file_kind), params.into_inner().unwrap_or_default())
 }
 
+#[post("/<name>/backfill?<params..>")]
+fn backfill_stats(
+    name: String,
+    params: Form<Option<BackfillParams>>,
+    _admin: Admin,
+    worker: State<Arc<Worker>>,
+) -> JsonResult {
+    let name = parse_name_param(&name, get_package_names(&worker))?;
+    let params = params.into_inner().unwrap_or_default();
+
+    let interval = match (params.every, params.monthly) {
+        (Some(every), None) | (Some(every), Some(false)) if every > 0 => BackfillInterval::Commits(every),
+        (None, None) | (None, Some(true)) => BackfillInterval::Monthly,
+        _ => {
+            return JsonResult::Err(
+                Some(json!({
+                    "name": name,
+                    "error": "Backfill requires either a positive every or monthly=true",
+                })),
+                Status::BadRequest,
+            )
+        },
+    };
+
+    let (branch, snapshots) =
+        match RUNTIME.block_on(worker.list_backfill_snapshots(&name, params.branch.as_deref(), &interval)) {
+            Ok(result) => result,
+            Err(error) => {
+                return JsonResult::Err(
+                    Some(json!({
+                        "name": name,
+                        "error": error,
+                    })),
+                    Status::BadRequest,
+                )
+            },
+        };
+    if snapshots.is_empty() {
+        return JsonResult::Ok(json!({
+            "name": name,
+            "branch": branch,
+            "snapshots": snapshots,
+        }));
+    }
+
+    // Listing every snapshot can take a while on long histories, so it's left to the job
+    let job_worker = (*worker).clone();
+    let job_name = name.clone();
+    let job_branch = branch.clone();
+    let job_snapshots = snapshots.clone();
+    let recursive = params.recursive.unwrap_or(false);
+    RUNTIME.spawn(async move {
+        let result = job_worker
+            .build_backfill_tasks(&job_name, &job_branch, &job_snapshots, recursive)
+            .await;
+        match result {
+            Ok((_, futures)) => spawn_tasks(job_worker.clone(), job_name, futures),
+            Err(err) => error!(job_worker.logger, "Error building backfill tasks: {}", err; "package" => job_name),
+        }
+    });
+
+    JsonResult::Err(
+        Some(json!({
+            "name": name,
+            "branch": branch,
+            "snapshots": snapshots,
+        })),
+        Status::Accepted,
+    )
+}
+
+#[get("/stats?<params..>")]
+fn get_aggregate_stats(params: Form<Option<AggregateParams>>, conn: DbConn, worker: State<Arc<Worker>>) -> JsonResult {
+    let params = params.into_inner().unwrap_or_default();
+    let parse_error = |err: String| (Some(json!({ "error": err })), Status::BadRequest);
+    let file_kind = match params.kind {
+        Some(ref kind) => Some(FileKind::from_string(kind).map_err(parse_error)?),
+        None => None,
+    };
+    let stat_kind = match params.stat_kind {
+        Some(ref stat_kind) => Some(StatKind::from_string(stat_kind).map_err(parse_error)?),
+        None => None,
+    };
+
+    let packages = worker.packages.read().unwrap().clone();
+    let filters = AggregateFilters {
+        topic: params.topic.as_deref(),
+        file_kind: file_kind.as_ref(),
+        stat_kind: stat_kind.as_ref(),
+    };
+    let (packages, totals) = RUNTIME
+        .block_on(aggregate::get_aggregate_stats(
+            &worker,
+            &conn,
+            &worker.logger,
+            &packages,
+            &filters,
+        ))
+        .map_err(|err| handle_db_error(&worker.logger, err))?;
+
+    JsonResult::Ok(json!({
+        "packages": packages,
+        "totals": totals,
+        "as_of": *worker.packages_updated.read().unwrap(),
+    }))
+}
+
 #[get("/packages")]
 fn get_all_packages(worker: State<Arc<Worker>>) -> JsonResult {
     get_packages(worker, None)
@@ -398,17 +845,23 @@ fn rocket(pool: db::Pool, worker: Arc<Worker>, logger: Logger, package_listing_r
         openapi_yaml,
         get_stats,
         get_specific_stats,
+        get_errors,
+        get_history,
+        get_specific_history,
+        compare_stats,
         calculate_stats,
         calculate_specific_stats,
+        backfill_stats,
         get_all_packages,
         get_specific_packages,
         update_all_packages,
         update_specific_packages,
+        get_aggregate_stats,
     ];
     if !package_listing_routes_enabled {
         routes = routes
             .into_iter()
-            .filter(|route| !route.uri.path().starts_with("/packages"))
+            .filter(|route| !route.uri.path().starts_with("/packages") && route.uri.path() != "/stats")
             .collect();
     }
 
@@ -458,29 +911,24 @@ fn start_package_update_loop(worker: Arc<Worker>) {
     });
 }
 
-pub fn service(
-    database_url: String,
-    github_auth_token: Option<&str>,
-    github_graphql_api_endpoint: Option<&str>,
-) -> rocket::Rocket {
+pub fn service(database_url: String, source: Arc<dyn SourceProvider>) -> rocket::Rocket {
     let pool = db::init_pool(&database_url);
     let logger = create_logger();
-    let worker = Arc::new(Worker::new(
-        pool.clone(),
-        logger.clone(),
-        github_auth_token.map(str::to_owned),
-        github_graphql_api_endpoint
-            .unwrap_or(GITHUB_GRAPHQL_API_ENDPOINT)
-            .to_owned(),
-    ));
+    let package_listing_routes_enabled = source.can_list_packages();
+    let worker = Arc::new(Worker::new(pool.clone(), logger.clone(), source));
 
-    let package_listing_routes_enabled = match github_auth_token {
-        Some(_) => {
-            start_package_update_loop(worker.clone());
-            true
+    match worker.resume_tasks() {
+        Ok(resumed_tasks) => {
+            for (name, futures) in resumed_tasks {
+                spawn_tasks(worker.clone(), name, futures);
+            }
         },
-        None => false,
-    };
+        Err(err) => error!(logger, "Failed to resume tasks: {}", err),
+    }
+
+    if package_listing_routes_enabled {
+        start_package_update_loop(worker.clone());
+    }
 
     rocket(pool, worker, logger, package_listing_routes_enabled)
 }
@@ -489,11 +937,38 @@ pub fn service(
 fn main() {
     dotenv().ok();
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let git_mirrors_path = || PathBuf::from(env::var("GIT_MIRRORS_PATH").expect("GIT_MIRRORS_PATH must be set"));
 
-    let github_auth_token = env::var("GITHUB_AUTH_TOKEN").map(Some).unwrap_or_default();
-    if github_auth_token.is_none() {
-        eprintln!("GITHUB_AUTH_TOKEN environment variable not set -- /packages route will be unavailable");
+    let source: Arc<dyn SourceProvider> = match env::var("SOURCE_PROVIDER").as_deref() {
+        Ok("github") | Err(_) => {
+            let github_auth_token = env::var("GITHUB_AUTH_TOKEN").map(Some).unwrap_or_default();
+            if github_auth_token.is_none() {
+                eprintln!("GITHUB_AUTH_TOKEN environment variable not set -- /packages route will be unavailable");
+            }
+
+            Arc::new(GitHubProvider::new(
+                git_mirrors_path(),
+                github_auth_token,
+                GITHUB_GRAPHQL_API_ENDPOINT.to_string(),
+            ))
+        },
+        Ok("git") => Arc::new(GitProvider::new(
+            env::var("GIT_REMOTE_ROOT").unwrap_or_else(|_| ORGANIZATION_ROOT.to_string()),
+            git_mirrors_path(),
+        )),
+        Ok("local") => Arc::new(LocalProvider::new(PathBuf::from(
+            env::var("LOCAL_PACKAGES_PATH").expect("LOCAL_PACKAGES_PATH must be set"),
+        ))),
+        Ok(provider) => panic!("Invalid SOURCE_PROVIDER: {}", provider),
+    };
+
+    let mut rocket = service(database_url, source);
+    match env::var("ADMIN_AUTH_TOKEN") {
+        Ok(admin_auth_token) => rocket = rocket.manage(AdminAuthToken(admin_auth_token)),
+        Err(_) => {
+            eprintln!("ADMIN_AUTH_TOKEN environment variable not set -- /<name>/backfill route will be unavailable")
+        },
     }
 
-    service(database_url, github_auth_token.as_deref(), None).launch();
+    rocket.launch();
 }
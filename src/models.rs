@@ -4,19 +4,30 @@ use chrono::NaiveDateTime;
 use diesel_derive_enum::DbEnum;
 use serde_derive::Serialize;
 
-use crate::{schema::entries, util::JsonValue};
+use crate::{
+    schema::{entries, tasks},
+    util::JsonValue,
+};
 
-#[derive(PartialEq, Clone, Debug, Serialize, DbEnum)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, DbEnum)]
 pub enum FileKind {
-    Monodix,     // emits Stems, Paradigms
-    Bidix,       // emits Entries
-    MetaMonodix, // emits Entries, Paradigms
-    MetaBidix,   // emits Entries
-    Postdix,     // emits Entries
+    Monodix,     // emits Stems*, *Paradigm*, *Symbols, EntriesBy*, (Regex|Multiword|Ignored|*Duplicate)Entries
+    Bidix,       // emits (|Multiword|Ignored|*Duplicate)Entries, EntriesBy*, *Symbols, Ambiguous*Lemmas, *FanOut
+    MetaMonodix, // emits the same as Monodix
+    MetaBidix,   // emits the same as Bidix
+    Postdix,     // emits the same as Bidix
     Rlx,         // emits Rules
-    Transfer,    // emits Rules, Macros
+    Transfer,    // superseded by T1x-T4x, kept for existing entries
+    T1x,         // emits Rules, Macros, Categories, Attributes, Variables, Lists, RulePatternLengths, UnusedMacros
+    T2x,         // emits the same as T1x
+    T3x,         // emits the same as T1x
+    T4x,         // emits the same as T1x
+    Rtx,         // emits Rules, OutputPatterns, Attributes, Macros
+    Lrx,         // emits Rules, CoveredLemmas, RuleWeights
+    Lsx,         // emits SeparableEntries, LongestEntry
+    Arx,         // emits Markables, Parameters
     Lexc,        // emits Stems, VanillaStems
-    Twol,        // emits Rules
+    Twol,        // emits Rules, AlphabetSize, Sets, Definitions, Diacritics
     Lexd,        // emits Lexicons, LexiconEntries, Patterns, PatternEntries
 }
 
@@ -29,7 +40,15 @@ impl FileKind {
             "metabidix" => Ok(FileKind::MetaBidix),
             "postdix" => Ok(FileKind::Postdix),
             "rlx" => Ok(FileKind::Rlx),
-            "transfer" => Ok(FileKind::Transfer),
+            "transfer" => Err("Transfer files are split into t1x, t2x, t3x and t4x kinds".to_string()),
+            "t1x" => Ok(FileKind::T1x),
+            "t2x" => Ok(FileKind::T2x),
+            "t3x" => Ok(FileKind::T3x),
+            "t4x" => Ok(FileKind::T4x),
+            "rtx" => Ok(FileKind::Rtx),
+            "lrx" => Ok(FileKind::Lrx),
+            "lsx" => Ok(FileKind::Lsx),
+            "arx" => Ok(FileKind::Arx),
             "lexc" => Ok(FileKind::Lexc),
             "twol" => Ok(FileKind::Twol),
             "lexd" => Ok(FileKind::Lexd),
@@ -44,18 +63,116 @@ impl fmt::Display for FileKind {
     }
 }
 
-#[derive(PartialEq, Clone, Debug, Serialize, DbEnum)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, DbEnum)]
 pub enum StatKind {
     Entries,
+    EntriesByDirection,
+    EntriesBySectionType,
+    RegexEntries,
+    MultiwordEntries,
+    EntriesByVariant,
+    IgnoredEntries,
+    DuplicateEntries,
+    NearDuplicateEntries,
+    AmbiguousLeftLemmas,
+    AmbiguousRightLemmas,
+    LeftFanOut,
+    RightFanOut,
     Paradigms,
+    ParadigmUsage,
+    UnusedParadigms,
+    UndefinedParadigms,
     Rules,
     Macros,
     Stems,
+    StemsByPos,
     VanillaStems,
     Lexicons,
     LexiconEntries,
     Patterns,
     PatternEntries,
+    Categories,
+    Attributes,
+    Variables,
+    Lists,
+    RulePatternLengths,
+    UnusedMacros,
+    OutputPatterns,
+    CoveredLemmas,
+    RuleWeights,
+    SeparableEntries,
+    LongestEntry,
+    Markables,
+    Parameters,
+    AlphabetSize,
+    Sets,
+    Definitions,
+    Diacritics,
+    Symbols,
+    UnusedSymbols,
+    UndefinedSymbols,
+}
+
+impl StatKind {
+    pub fn from_string(s: &str) -> Result<StatKind, String> {
+        match s.to_lowercase().replace("_", "").as_ref() {
+            "entries" => Ok(StatKind::Entries),
+            "entriesbydirection" => Ok(StatKind::EntriesByDirection),
+            "entriesbysectiontype" => Ok(StatKind::EntriesBySectionType),
+            "regexentries" => Ok(StatKind::RegexEntries),
+            "multiwordentries" => Ok(StatKind::MultiwordEntries),
+            "entriesbyvariant" => Ok(StatKind::EntriesByVariant),
+            "ignoredentries" => Ok(StatKind::IgnoredEntries),
+            "duplicateentries" => Ok(StatKind::DuplicateEntries),
+            "nearduplicateentries" => Ok(StatKind::NearDuplicateEntries),
+            "ambiguousleftlemmas" => Ok(StatKind::AmbiguousLeftLemmas),
+            "ambiguousrightlemmas" => Ok(StatKind::AmbiguousRightLemmas),
+            "leftfanout" => Ok(StatKind::LeftFanOut),
+            "rightfanout" => Ok(StatKind::RightFanOut),
+            "paradigms" => Ok(StatKind::Paradigms),
+            "paradigmusage" => Ok(StatKind::ParadigmUsage),
+            "unusedparadigms" => Ok(StatKind::UnusedParadigms),
+            "undefinedparadigms" => Ok(StatKind::UndefinedParadigms),
+            "rules" => Ok(StatKind::Rules),
+            "macros" => Ok(StatKind::Macros),
+            "stems" => Ok(StatKind::Stems),
+            "stemsbypos" => Ok(StatKind::StemsByPos),
+            "vanillastems" => Ok(StatKind::VanillaStems),
+            "lexicons" => Ok(StatKind::Lexicons),
+            "lexiconentries" => Ok(StatKind::LexiconEntries),
+            "patterns" => Ok(StatKind::Patterns),
+            "patternentries" => Ok(StatKind::PatternEntries),
+            "categories" => Ok(StatKind::Categories),
+            "attributes" => Ok(StatKind::Attributes),
+            "variables" => Ok(StatKind::Variables),
+            "lists" => Ok(StatKind::Lists),
+            "rulepatternlengths" => Ok(StatKind::RulePatternLengths),
+            "unusedmacros" => Ok(StatKind::UnusedMacros),
+            "outputpatterns" => Ok(StatKind::OutputPatterns),
+            "coveredlemmas" => Ok(StatKind::CoveredLemmas),
+            "ruleweights" => Ok(StatKind::RuleWeights),
+            "separableentries" => Ok(StatKind::SeparableEntries),
+            "longestentry" => Ok(StatKind::LongestEntry),
+            "markables" => Ok(StatKind::Markables),
+            "parameters" => Ok(StatKind::Parameters),
+            "alphabetsize" => Ok(StatKind::AlphabetSize),
+            "sets" => Ok(StatKind::Sets),
+            "definitions" => Ok(StatKind::Definitions),
+            "diacritics" => Ok(StatKind::Diacritics),
+            "symbols" => Ok(StatKind::Symbols),
+            "unusedsymbols" => Ok(StatKind::UnusedSymbols),
+            "undefinedsymbols" => Ok(StatKind::UndefinedSymbols),
+            _ => Err(format!("Invalid stat kind: {}", s)),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, DbEnum)]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
 }
 
 #[derive(QueryableByName, Queryable, Serialize)]
@@ -76,6 +193,7 @@ pub struct Entry {
     pub file_kind: FileKind,
     pub stat_kind: StatKind,
     pub value: JsonValue,
+    pub branch: String,
 }
 
 #[derive(Clone, Insertable, Debug, Serialize)]
@@ -93,4 +211,44 @@ pub struct NewEntry {
     pub file_kind: FileKind,
     pub stat_kind: StatKind,
     pub value: JsonValue,
+    pub branch: String,
+}
+
+#[derive(QueryableByName, Queryable)]
+#[table_name = "tasks"]
+pub struct Task {
+    pub id: i32,
+    pub name: String,
+    pub branch: String,
+    pub path: String,
+    pub file_kind: FileKind,
+    pub revision: i32,
+    pub sha: String,
+    pub blob: String,
+    pub size: i32,
+    pub last_author: String,
+    pub last_changed: NaiveDateTime,
+    pub status: TaskStatus,
+    pub error: Option<String>,
+    pub created: NaiveDateTime,
+    pub started: Option<NaiveDateTime>,
+    pub completed: Option<NaiveDateTime>,
+    pub error_kind: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "tasks"]
+pub struct NewTask {
+    pub name: String,
+    pub branch: String,
+    pub path: String,
+    pub file_kind: FileKind,
+    pub revision: i32,
+    pub sha: String,
+    pub blob: String,
+    pub size: i32,
+    pub last_author: String,
+    pub last_changed: NaiveDateTime,
+    pub status: TaskStatus,
+    pub created: NaiveDateTime,
 }
@@ -17,5 +17,33 @@ table! {
         file_kind -> FileKindMapping,
         stat_kind -> StatKindMapping,
         value -> JsonType,
+        branch -> Text,
     }
 }
+
+table! {
+    use diesel::sql_types::{Integer, Nullable, Timestamp, Text};
+    use crate::models::{FileKindMapping, TaskStatusMapping};
+
+    tasks (id) {
+        id -> Integer,
+        name -> Text,
+        branch -> Text,
+        path -> Text,
+        file_kind -> FileKindMapping,
+        revision -> Integer,
+        sha -> Text,
+        blob -> Text,
+        size -> Integer,
+        last_author -> Text,
+        last_changed -> Timestamp,
+        status -> TaskStatusMapping,
+        error -> Nullable<Text>,
+        created -> Timestamp,
+        started -> Nullable<Timestamp>,
+        completed -> Nullable<Timestamp>,
+        error_kind -> Nullable<Text>,
+    }
+}
+
+allow_tables_to_appear_in_same_query!(entries, tasks);
@@ -0,0 +1,431 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Output,
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use futures::{
+    future::{join_all, BoxFuture},
+    FutureExt,
+};
+use slog::{debug, error, trace, warn, Logger};
+use tokio::process::Command;
+
+use crate::{
+    sources::SourceProvider,
+    stats::{get_file_kind, StatsError},
+    worker::{Actor, Commit, File, Package},
+    PACKAGE_UPDATE_FALLBACK_INTERVAL,
+};
+
+type LastCommit = (String, String, NaiveDateTime);
+
+const COMMIT_FORMAT: &str = "--format=%H%x1f%an%x1f%ae%x1f%cn%x1f%ce%x1f%at%x1f%ct%x1f%s";
+
+async fn run_git(repo_path: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output();
+
+    match output.await {
+        Ok(Output { status, ref stdout, .. }) if status.success() => Ok(String::from_utf8_lossy(stdout).into_owned()),
+        Ok(Output { stderr, .. }) => Err(String::from_utf8_lossy(&stderr).trim().to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+async fn get_revision(repo_path: &Path, sha: &str) -> Result<i32, String> {
+    run_git(repo_path, &["rev-list", "--count", sha])
+        .await?
+        .trim()
+        .parse::<i32>()
+        .map_err(|err| format!("Revision number parsing error for {}: {:?}", sha, err))
+}
+
+fn parse_last_commits(log: &str, paths: &HashSet<&str>) -> Result<HashMap<String, LastCommit>, String> {
+    let mut last_commits = HashMap::new();
+    let mut current_commit: Option<LastCommit> = None;
+
+    for line in log.lines() {
+        if let Some(header) = line.strip_prefix('\x1e') {
+            current_commit = match header.split('\x1f').collect::<Vec<_>>().as_slice() {
+                [sha, author, timestamp] => {
+                    let timestamp = timestamp
+                        .parse::<i64>()
+                        .map_err(|err| format!("Commit timestamp parsing error for {}: {:?}", sha, err))?;
+                    Some((
+                        sha.to_string(),
+                        author.to_string(),
+                        NaiveDateTime::from_timestamp(timestamp, 0),
+                    ))
+                },
+                _ => return Err(format!("Malformed commit header: {:?}", header)),
+            };
+        } else if paths.contains(line) && !last_commits.contains_key(line) {
+            if let Some(ref commit) = current_commit {
+                last_commits.insert(line.to_string(), commit.clone());
+                if last_commits.len() == paths.len() {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(last_commits)
+}
+
+pub async fn update_mirror(
+    logger: &Logger,
+    remote_root: &str,
+    mirrors_path: &Path,
+    package_name: &str,
+) -> Result<PathBuf, String> {
+    let repo_name = format!("{}.git", package_name);
+    let repo_path = mirrors_path.join(&repo_name);
+
+    if repo_path.exists() {
+        debug!(logger, "Fetching mirror"; "path" => repo_path.display().to_string());
+        run_git(&repo_path, &["fetch", "--prune", "--quiet", "origin"])
+            .await
+            .map_err(|err| format!("Package fetch failed: {}", err))?;
+
+        // Mirrors don't follow changes to the remote's default branch on fetch
+        let remote_head = run_git(&repo_path, &["ls-remote", "--symref", "origin", "HEAD"])
+            .await
+            .map_err(|err| format!("Package fetch failed: {}", err))?;
+        if let Some(head_ref) = remote_head
+            .lines()
+            .find_map(|line| line.strip_prefix("ref: ").and_then(|line| line.split('\t').next()))
+        {
+            run_git(&repo_path, &["symbolic-ref", "HEAD", head_ref])
+                .await
+                .map_err(|err| format!("Package fetch failed: {}", err))?;
+        }
+    } else {
+        debug!(logger, "Cloning mirror"; "path" => repo_path.display().to_string());
+        fs::create_dir_all(mirrors_path).map_err(|err| format!("Package search failed: {}", err))?;
+        let url = format!("{}/{}", remote_root, repo_name);
+        run_git(mirrors_path, &["clone", "--mirror", "--quiet", &url, &repo_name])
+            .await
+            .map_err(|err| format!("Package not found: {}", err))?;
+    }
+
+    Ok(repo_path)
+}
+
+// Revisions can come straight from request parameters, so they mustn't be mistaken for options
+pub async fn resolve_revision(repo_path: &Path, revision: &str) -> Result<String, String> {
+    if revision.starts_with('-') {
+        return Err(format!("Invalid revision: {}", revision));
+    }
+
+    run_git(
+        repo_path,
+        &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", revision)],
+    )
+    .await
+    .map(|sha| sha.trim().to_string())
+    .map_err(|_| format!("Revision not found: {}", revision))
+}
+
+pub async fn list_files(
+    logger: &Logger,
+    repo_path: &Path,
+    revision: &str,
+    recursive: bool,
+) -> Result<Vec<File>, String> {
+    let revision = resolve_revision(repo_path, revision).await?;
+    let mut ls_tree_args = vec!["ls-tree", "-l", "-z"];
+    if recursive {
+        ls_tree_args.push("-r");
+    }
+    ls_tree_args.push(&revision);
+    let listing = run_git(repo_path, &ls_tree_args)
+        .await
+        .map_err(|err| format!("Package not found: {}", err))?;
+
+    let mut blobs = Vec::new();
+    for entry in listing.split('\0').filter(|entry| !entry.is_empty()) {
+        let mut split = entry.splitn(2, '\t');
+        let (metadata, path) = match (split.next(), split.next()) {
+            (Some(metadata), Some(path)) => (metadata, path),
+            _ => return Err(format!("Malformed tree entry: {:?}", entry)),
+        };
+
+        if get_file_kind(path).is_none() {
+            continue;
+        }
+
+        if let [_, "blob", blob, size] = metadata.split_whitespace().collect::<Vec<_>>().as_slice() {
+            let size = size
+                .parse::<i32>()
+                .map_err(|err| format!("File size number parsing error for {}: {:?}", path, err))?;
+            blobs.push((path.to_string(), blob.to_string(), size));
+        }
+    }
+
+    if blobs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Following first parents attributes changes merged in from other branches to their merge commits
+    let mut log_args = vec![
+        "-c",
+        "core.quotePath=false",
+        "log",
+        "--first-parent",
+        "-m",
+        "--format=%x1e%H%x1f%an%x1f%ct",
+        "--name-only",
+        &revision,
+        "--",
+    ];
+    log_args.extend(blobs.iter().map(|(path, ..)| path.as_str()));
+
+    let log = run_git(repo_path, &log_args)
+        .await
+        .map_err(|err| format!("Package history search failed: {}", err))?;
+    let mut last_commits = parse_last_commits(&log, &blobs.iter().map(|(path, ..)| path.as_str()).collect())?;
+
+    let mut unique_shas = last_commits.values().map(|(sha, ..)| sha.clone()).collect::<Vec<_>>();
+    unique_shas.sort_unstable();
+    unique_shas.dedup();
+    debug!(logger, "Found {} unique commits", unique_shas.len());
+
+    let revisions = join_all(unique_shas.iter().map(|sha| get_revision(repo_path, sha))).await;
+    let sha_revision_mapping = unique_shas.into_iter().zip(revisions).collect::<HashMap<_, _>>();
+
+    Ok(blobs
+        .into_iter()
+        .filter_map(|(path, blob, size)| match last_commits.remove(&path) {
+            Some((sha, last_author, last_changed)) => match sha_revision_mapping.get(&sha) {
+                Some(Ok(revision)) => {
+                    trace!(
+                        logger,
+                        "Parsed file";
+                        "name" => path.clone(), "size" => size, "revision" => revision, "author" => last_author.clone(), "date" => last_changed.to_string(),
+                    );
+                    Some(File {
+                        path,
+                        size,
+                        revision: *revision,
+                        sha,
+                        blob,
+                        last_author,
+                        last_changed,
+                    })
+                },
+                Some(Err(err)) => {
+                    error!(logger, "Error getting revision corresponding to SHA: {:?}", err; "path" => path, "sha" => sha);
+                    None
+                },
+                None => None,
+            },
+            None => {
+                error!(logger, "Failed to find last commit for file"; "path" => path);
+                None
+            },
+        })
+        .collect())
+}
+
+pub async fn get_default_branch(repo_path: &Path) -> Result<String, String> {
+    run_git(repo_path, &["symbolic-ref", "--short", "HEAD"])
+        .await
+        .map(|branch| branch.trim().to_string())
+        .map_err(|err| format!("Default branch not found: {}", err))
+}
+
+pub async fn show_file(repo_path: &Path, file: &File) -> Result<String, StatsError> {
+    run_git(repo_path, &["cat-file", "blob", &file.blob])
+        .await
+        .map_err(StatsError::Git)
+}
+
+fn parse_commit(line: &str) -> Result<Commit, String> {
+    match line.split('\x1f').collect::<Vec<_>>().as_slice() {
+        [sha, author_name, author_email, committer_name, committer_email, authored, committed, message] => {
+            let parse_timestamp = |timestamp: &str| {
+                timestamp
+                    .parse::<i64>()
+                    .map(|timestamp| NaiveDateTime::from_timestamp(timestamp, 0))
+                    .map_err(|err| format!("Commit timestamp parsing error for {}: {:?}", sha, err))
+            };
+
+            Ok(Commit {
+                sha: sha.to_string(),
+                message: message.to_string(),
+                authored: parse_timestamp(authored)?,
+                committed: parse_timestamp(committed)?,
+                author: Actor {
+                    name: author_name.to_string(),
+                    email: author_email.to_string(),
+                },
+                committer: Actor {
+                    name: committer_name.to_string(),
+                    email: committer_email.to_string(),
+                },
+            })
+        },
+        _ => Err(format!("Malformed commit: {:?}", line)),
+    }
+}
+
+pub async fn get_last_commit(repo_path: &Path) -> Result<Commit, String> {
+    let log = run_git(repo_path, &["log", "-1", COMMIT_FORMAT, "HEAD", "--"]).await?;
+    parse_commit(log.trim_end())
+}
+
+pub async fn list_commits(repo_path: &Path, revision: &str) -> Result<Vec<Commit>, String> {
+    let revision = resolve_revision(repo_path, revision).await?;
+    run_git(repo_path, &["log", "--first-parent", COMMIT_FORMAT, &revision, "--"])
+        .await
+        .map_err(|err| format!("Package history search failed: {}", err))?
+        .lines()
+        .map(parse_commit)
+        .collect()
+}
+
+pub async fn get_packages(logger: &Logger, repo_paths: Vec<(String, PathBuf)>) -> Vec<Package> {
+    let last_commits = join_all(repo_paths.iter().map(|(_, repo_path)| get_last_commit(repo_path))).await;
+    let default_branches = join_all(repo_paths.iter().map(|(_, repo_path)| get_default_branch(repo_path))).await;
+
+    repo_paths
+        .into_iter()
+        .zip(last_commits.into_iter().zip(default_branches))
+        .map(|((name, _), (last_commit, default_branch))| Package {
+            last_commit: last_commit
+                .map_err(|err| warn!(logger, "Failed to get last commit: {}", err; "package" => name.clone()))
+                .ok(),
+            default_branch: default_branch
+                .map_err(|err| warn!(logger, "Failed to get default branch: {}", err; "package" => name.clone()))
+                .ok(),
+            name,
+            description: None,
+            topics: vec![],
+        })
+        .collect()
+}
+
+pub struct GitProvider {
+    remote_root: String,
+    mirrors_path: PathBuf,
+}
+
+impl GitProvider {
+    pub fn new(remote_root: String, mirrors_path: PathBuf) -> GitProvider {
+        GitProvider {
+            remote_root,
+            mirrors_path,
+        }
+    }
+
+    fn get_mirror_paths(&self) -> Result<Vec<(String, PathBuf)>, failure::Error> {
+        if !self.mirrors_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut mirror_paths = Vec::new();
+        for dir_entry in fs::read_dir(&self.mirrors_path)? {
+            let path = dir_entry?.path();
+            if let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".git"))
+            {
+                mirror_paths.push((name.to_string(), path.clone()));
+            }
+        }
+
+        Ok(mirror_paths)
+    }
+}
+
+impl SourceProvider for GitProvider {
+    fn list_packages<'a>(
+        &'a self,
+        logger: &'a Logger,
+    ) -> BoxFuture<'a, Result<(Vec<Package>, Duration), failure::Error>> {
+        async move {
+            let packages = get_packages(logger, self.get_mirror_paths()?).await;
+            Ok((packages, PACKAGE_UPDATE_FALLBACK_INTERVAL))
+        }
+        .boxed()
+    }
+
+    fn get_default_branch<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+    ) -> BoxFuture<'a, Result<String, String>> {
+        async move {
+            let mut repo_path = self.mirrors_path.join(format!("{}.git", package_name));
+            if !repo_path.exists() {
+                repo_path = update_mirror(logger, &self.remote_root, &self.mirrors_path, package_name).await?;
+            }
+
+            get_default_branch(&repo_path).await
+        }
+        .boxed()
+    }
+
+    fn list_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        async move {
+            let repo_path = update_mirror(logger, &self.remote_root, &self.mirrors_path, package_name).await?;
+            list_files(logger, &repo_path, revision, recursive).await
+        }
+        .boxed()
+    }
+
+    fn list_fetched_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        async move {
+            let repo_path = self.mirrors_path.join(format!("{}.git", package_name));
+            list_files(logger, &repo_path, revision, recursive).await
+        }
+        .boxed()
+    }
+
+    fn list_commits<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Commit>, String>> {
+        async move {
+            let repo_path = update_mirror(logger, &self.remote_root, &self.mirrors_path, package_name).await?;
+            list_commits(&repo_path, revision).await
+        }
+        .boxed()
+    }
+
+    fn fetch_file<'a>(
+        &'a self,
+        _logger: &'a Logger,
+        package_name: &'a str,
+        file: &'a File,
+    ) -> BoxFuture<'a, Result<String, StatsError>> {
+        async move {
+            let repo_path = self.mirrors_path.join(format!("{}.git", package_name));
+            show_file(&repo_path, file).await
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashSet, path::PathBuf, time::Duration};
+
+use chrono::Utc;
+use failure::Fail;
+use futures::{future::BoxFuture, FutureExt};
+use graphql_client::{GraphQLQuery, Response};
+use lazy_static::lazy_static;
+use slog::{debug, info, warn, Logger};
+
+use crate::{
+    sources::{GitProvider, SourceProvider},
+    stats::StatsError,
+    worker::{Actor, Commit, File, Package},
+    HTTPS_CLIENT, ORGANIZATION_ROOT,
+};
+
+type DateTime = chrono::DateTime<Utc>;
+type GitObjectID = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/schema.json",
+    query_path = "src/graphql/packages_query.graphql",
+    response_derives = "Clone, Debug"
+)]
+pub struct PackagesQuery;
+
+#[derive(Debug, Fail)]
+enum PackageUpdateError {
+    #[fail(display = "Missing response data key: {}", _0)]
+    MissingData(String),
+}
+
+async fn get_packages(
+    logger: &Logger,
+    github_auth_token: &str,
+    github_graphql_api_endpoint: &str,
+    after: Option<&String>,
+) -> Result<(Vec<Package>, Option<String>, packages_query::PackagesQueryRateLimit), failure::Error> {
+    lazy_static! {
+        static ref TOPICS: HashSet<&'static str> = [
+            "apertium-languages",
+            "apertium-incubator",
+            "apertium-nursery",
+            "apertium-staging",
+            "apertium-trunk"
+        ]
+        .iter()
+        .cloned()
+        .collect();
+    }
+
+    debug!(logger, "Fetching repos"; "after" => after);
+
+    let query = PackagesQuery::build_query(packages_query::Variables { after: after.cloned() });
+    let response: Response<packages_query::ResponseData> = HTTPS_CLIENT
+        .post(github_graphql_api_endpoint)
+        .bearer_auth(github_auth_token)
+        .json(&query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if let Some(errors) = response.errors {
+        for err in &errors {
+            warn!(logger, "Encountered error while fetching repos: {:?}", err; "after" => after);
+        }
+    }
+
+    let response_data: packages_query::ResponseData = response
+        .data
+        .ok_or_else(|| PackageUpdateError::MissingData("data".to_string()))?;
+
+    let repositories = response_data
+        .organization
+        .ok_or_else(|| PackageUpdateError::MissingData("data.organization".to_string()))?
+        .repositories;
+
+    let packages = repositories
+        .edges
+        .ok_or_else(|| PackageUpdateError::MissingData("data.organization.repository.edges".to_string()))?
+        .into_iter()
+        .filter_map(|repo_node| {
+            if repo_node.is_none() {
+                warn!(logger, "Missing repository node");
+            }
+
+            repo_node.and_then(|x| x.node).map(|repo| Package {
+                name: repo.name,
+                description: repo.description,
+                topics: repo.repository_topics.nodes.map_or(vec![], |topics| {
+                    topics
+                        .into_iter()
+                        .filter_map(|topic| topic.map(|x| x.topic).map(|x| x.name))
+                        .collect()
+                }),
+                default_branch: repo.default_branch_ref.as_ref().map(|x| x.name.clone()),
+                last_commit: repo.default_branch_ref.and_then(|x| match x.target.on {
+                    packages_query::PackagesQueryOrganizationRepositoriesEdgesNodeDefaultBranchRefTargetOn::Commit(commit) => commit
+                        .history
+                        .edges
+                        .and_then(|x| x.first().and_then(|y| y.clone().and_then(|z| z.node)))
+                        .and_then(|ref commit| match (&commit.author, &commit.committer) {
+                            (Some(ref author), Some(ref committer)) =>
+                                match (&author.name, &author.email, &committer.name, &committer.email) {
+                                    (Some(ref author_name), Some(ref author_email), Some(ref committer_name), Some(ref committer_email)) =>
+                                        Some(Commit {
+                                            sha: commit.oid.clone(),
+                                            message: commit.clone().message_headline,
+                                            authored: commit.authored_date.naive_utc(),
+                                            committed: commit.committed_date.naive_utc(),
+                                            author: Actor {
+                                                name: author_name.to_string(),
+                                                email: author_email.to_string(),
+                                            },
+                                            committer: Actor {
+                                                name: committer_name.to_string(),
+                                                email: committer_email.to_string(),
+                                            },
+                                        }),
+                                    _ => {
+                                        warn!(logger, "Commit author or committer missing information: author = {:?}, committer = {:?}", author, committer);
+                                        None
+                                    }
+                                }
+                            _ => {
+                                warn!(logger, "Commit missing author or committer: {:?}", commit);
+                                None
+                            },
+                        }),
+                    _ => None,
+                }),
+            })
+        })
+        .filter(|Package {topics, ..}| !TOPICS.is_disjoint(&topics.iter().map(|x| x.as_str()).collect()))
+        .collect::<Vec<_>>();
+
+    let page_info = repositories.page_info;
+    let next_after = if page_info.has_next_page {
+        page_info.end_cursor
+    } else {
+        None
+    };
+
+    let limits = response_data
+        .rate_limit
+        .ok_or_else(|| PackageUpdateError::MissingData("data.organization.rate_limit".to_string()))?;
+
+    debug!(logger, "Fetched {} packages", packages.len());
+
+    Ok((packages, next_after, limits))
+}
+
+pub struct GitHubProvider {
+    git: GitProvider,
+    auth_token: Option<String>,
+    graphql_api_endpoint: String,
+}
+
+impl GitHubProvider {
+    pub fn new(mirrors_path: PathBuf, auth_token: Option<String>, graphql_api_endpoint: String) -> GitHubProvider {
+        GitHubProvider {
+            git: GitProvider::new(ORGANIZATION_ROOT.to_string(), mirrors_path),
+            auth_token,
+            graphql_api_endpoint,
+        }
+    }
+}
+
+impl SourceProvider for GitHubProvider {
+    fn can_list_packages(&self) -> bool {
+        self.auth_token.is_some()
+    }
+
+    fn list_packages<'a>(
+        &'a self,
+        logger: &'a Logger,
+    ) -> BoxFuture<'a, Result<(Vec<Package>, Duration), failure::Error>> {
+        async move {
+            let auth_token = self
+                .auth_token
+                .as_ref()
+                .expect("package list update requires a GitHub auth token")
+                .as_str();
+            let mut packages = Vec::new();
+
+            let (mut new_packages, mut after, mut rate_limits) =
+                get_packages(logger, auth_token, &self.graphql_api_endpoint, None).await?;
+            let mut total_cost = rate_limits.cost;
+            packages.append(&mut new_packages);
+            while after.is_some() {
+                let (mut new_packages, new_after, new_rate_limits) =
+                    get_packages(logger, auth_token, &self.graphql_api_endpoint, after.as_ref()).await?;
+                after = new_after;
+                rate_limits = new_rate_limits;
+                total_cost += rate_limits.cost;
+                packages.append(&mut new_packages);
+            }
+
+            let next_update = (rate_limits.reset_at - Utc::now()) / ((rate_limits.remaining / total_cost) as i32);
+            info!(
+                logger,
+                "Fetched GitHub package list";
+                "total_cost" => total_cost, "cost_remaining" => rate_limits.remaining,
+                "next_update_min" => next_update.to_string()
+            );
+            Ok((packages, chrono::Duration::to_std(&next_update)?))
+        }
+        .boxed()
+    }
+
+    fn get_default_branch<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+    ) -> BoxFuture<'a, Result<String, String>> {
+        self.git.get_default_branch(logger, package_name)
+    }
+
+    fn list_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        self.git.list_files(logger, package_name, revision, recursive)
+    }
+
+    fn list_fetched_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        self.git.list_fetched_files(logger, package_name, revision, recursive)
+    }
+
+    fn list_commits<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Commit>, String>> {
+        self.git.list_commits(logger, package_name, revision)
+    }
+
+    fn fetch_file<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        file: &'a File,
+    ) -> BoxFuture<'a, Result<String, StatsError>> {
+        self.git.fetch_file(logger, package_name, file)
+    }
+}
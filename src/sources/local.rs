@@ -0,0 +1,107 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use futures::{future::BoxFuture, FutureExt};
+use slog::Logger;
+
+use crate::{
+    sources::{
+        git::{get_default_branch, get_packages, list_commits, list_files, show_file},
+        SourceProvider,
+    },
+    stats::StatsError,
+    worker::{Commit, File, Package},
+    PACKAGE_UPDATE_FALLBACK_INTERVAL,
+};
+
+pub struct LocalProvider {
+    root: PathBuf,
+}
+
+impl LocalProvider {
+    pub fn new(root: PathBuf) -> LocalProvider {
+        LocalProvider { root }
+    }
+
+    fn get_checkout_paths(&self) -> Result<Vec<(String, PathBuf)>, failure::Error> {
+        let mut checkout_paths = Vec::new();
+        for dir_entry in fs::read_dir(&self.root)? {
+            let path = dir_entry?.path();
+            if let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| name.starts_with("apertium-"))
+            {
+                if path.join(".git").exists() {
+                    checkout_paths.push((name.to_string(), path.clone()));
+                }
+            }
+        }
+
+        Ok(checkout_paths)
+    }
+}
+
+impl SourceProvider for LocalProvider {
+    fn list_packages<'a>(
+        &'a self,
+        logger: &'a Logger,
+    ) -> BoxFuture<'a, Result<(Vec<Package>, Duration), failure::Error>> {
+        async move {
+            let packages = get_packages(logger, self.get_checkout_paths()?).await;
+            Ok((packages, PACKAGE_UPDATE_FALLBACK_INTERVAL))
+        }
+        .boxed()
+    }
+
+    fn get_default_branch<'a>(
+        &'a self,
+        _logger: &'a Logger,
+        package_name: &'a str,
+    ) -> BoxFuture<'a, Result<String, String>> {
+        async move { get_default_branch(&self.root.join(package_name)).await }.boxed()
+    }
+
+    fn list_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        async move {
+            let repo_path = self.root.join(package_name);
+            if !repo_path.exists() {
+                return Err(format!("Package not found: {}", repo_path.display()));
+            }
+
+            list_files(logger, &repo_path, revision, recursive).await
+        }
+        .boxed()
+    }
+
+    fn list_commits<'a>(
+        &'a self,
+        _logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Commit>, String>> {
+        async move {
+            let repo_path = self.root.join(package_name);
+            if !repo_path.exists() {
+                return Err(format!("Package not found: {}", repo_path.display()));
+            }
+
+            list_commits(&repo_path, revision).await
+        }
+        .boxed()
+    }
+
+    fn fetch_file<'a>(
+        &'a self,
+        _logger: &'a Logger,
+        package_name: &'a str,
+        file: &'a File,
+    ) -> BoxFuture<'a, Result<String, StatsError>> {
+        async move { show_file(&self.root.join(package_name), file).await }.boxed()
+    }
+}
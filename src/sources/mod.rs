@@ -0,0 +1,65 @@
+mod git;
+mod github;
+mod local;
+
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use slog::Logger;
+
+use crate::{
+    stats::StatsError,
+    worker::{Commit, File, Package},
+};
+
+pub use self::{git::GitProvider, github::GitHubProvider, local::LocalProvider};
+
+pub trait SourceProvider: Send + Sync {
+    fn can_list_packages(&self) -> bool {
+        true
+    }
+
+    fn list_packages<'a>(
+        &'a self,
+        logger: &'a Logger,
+    ) -> BoxFuture<'a, Result<(Vec<Package>, Duration), failure::Error>>;
+
+    fn get_default_branch<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+    ) -> BoxFuture<'a, Result<String, String>>;
+
+    fn list_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>>;
+
+    // Listing revisions already fetched by list_commits shouldn't fetch the package again
+    fn list_fetched_files<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+        recursive: bool,
+    ) -> BoxFuture<'a, Result<Vec<File>, String>> {
+        self.list_files(logger, package_name, revision, recursive)
+    }
+
+    fn list_commits<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        revision: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Commit>, String>>;
+
+    fn fetch_file<'a>(
+        &'a self,
+        logger: &'a Logger,
+        package_name: &'a str,
+        file: &'a File,
+    ) -> BoxFuture<'a, Result<String, StatsError>>;
+}
@@ -1,69 +1,88 @@
 mod lexc;
 mod lexd;
 mod rlx;
+mod rtx;
+mod twol;
 mod xml;
 
 use std::{
+    fmt,
     io::{self},
     str::Utf8Error,
 };
 
 use lazy_static::lazy_static;
 use regex::{RegexSet, RegexSetBuilder};
-use reqwest::Error as ReqwestError;
-use rocket_contrib::{json, json::JsonValue};
+use rocket_contrib::json::JsonValue;
 use slog::Logger;
 
 use crate::{
     models::{FileKind, StatKind},
     util::LANG_CODE_RE,
-    HTTPS_CLIENT, ORGANIZATION_RAW_ROOT,
 };
 
 #[derive(Debug)]
 pub enum StatsError {
-    Reqwest(ReqwestError),
     Utf8(Utf8Error),
     Io(io::Error),
+    Git(String),
     Xml(String),
     Rlx(String),
+    Rtx(String),
     Lexd(String),
     Lexc(String),
+    Twol(String),
 }
 
-pub type StatsResults = Result<Vec<(StatKind, JsonValue)>, StatsError>;
+impl StatsError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StatsError::Utf8(_) => "Utf8",
+            StatsError::Io(_) => "Io",
+            StatsError::Git(_) => "Git",
+            StatsError::Xml(_) => "Xml",
+            StatsError::Rlx(_) => "Rlx",
+            StatsError::Rtx(_) => "Rtx",
+            StatsError::Lexd(_) => "Lexd",
+            StatsError::Lexc(_) => "Lexc",
+            StatsError::Twol(_) => "Twol",
+        }
+    }
+}
 
-pub async fn get_file_stats(
-    logger: Logger,
-    file_path: String,
-    package_name: String,
-    file_kind: FileKind,
-) -> StatsResults {
-    let url = format!("{}/{}/master/{}", ORGANIZATION_RAW_ROOT, package_name, file_path);
-    let logger = logger.clone();
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Utf8(err) => err.fmt(f),
+            StatsError::Io(err) => err.fmt(f),
+            StatsError::Git(message)
+            | StatsError::Xml(message)
+            | StatsError::Rlx(message)
+            | StatsError::Rtx(message)
+            | StatsError::Lexd(message)
+            | StatsError::Lexc(message)
+            | StatsError::Twol(message) => f.write_str(message),
+        }
+    }
+}
 
-    let body = HTTPS_CLIENT
-        .get(&url)
-        .send()
-        .await
-        .map_err(StatsError::Reqwest)?
-        .error_for_status()
-        .map_err(StatsError::Reqwest)?
-        .text()
-        .await
-        .map_err(StatsError::Reqwest)?;
+pub type StatsResults = Result<Vec<(StatKind, JsonValue)>, StatsError>;
 
+pub fn get_file_stats(logger: &Logger, body: &str, file_path: &str, file_kind: &FileKind) -> StatsResults {
     match file_kind {
-        FileKind::Monodix | FileKind::MetaMonodix => self::xml::get_monodix_stats(&body, &file_path),
-        FileKind::Bidix | FileKind::MetaBidix | FileKind::Postdix => self::xml::get_bidix_stats(&body, &file_path),
-        FileKind::Transfer => self::xml::get_transfer_stats(&body, &file_path),
-        FileKind::Rlx => self::rlx::get_stats(&logger, &body),
-        FileKind::Twol => {
-            let rule_count = body.lines().filter(|line| line.starts_with('"')).count();
-            Ok(vec![(StatKind::Rules, json!(rule_count))])
+        FileKind::Monodix | FileKind::MetaMonodix => self::xml::get_monodix_stats(body, file_path),
+        FileKind::Bidix | FileKind::MetaBidix | FileKind::Postdix => self::xml::get_bidix_stats(body, file_path),
+        FileKind::Transfer | FileKind::T1x | FileKind::T2x | FileKind::T3x | FileKind::T4x => {
+            self::xml::get_transfer_stats(body, file_path)
         },
-        FileKind::Lexc => self::lexc::get_stats(&logger, &body),
-        FileKind::Lexd => self::lexd::get_stats(&logger, &body),
+        FileKind::Rtx => self::rtx::get_stats(logger, body),
+        FileKind::Lrx => self::xml::get_lrx_stats(body, file_path),
+        FileKind::Lsx => self::xml::get_lsx_stats(body, file_path),
+        FileKind::Arx => self::xml::get_arx_stats(body, file_path),
+        FileKind::Rlx => self::rlx::get_stats(logger, body),
+        FileKind::Twol => self::twol::get_stats(logger, body),
+        FileKind::Lexc => self::lexc::get_stats(logger, body),
+        FileKind::Lexd => self::lexd::get_stats(logger, body),
     }
 }
 
@@ -79,9 +98,19 @@ pub fn get_file_kind(file_name: &str) -> Option<FileKind> {
                 format!(r"apertium-{re}-{re}\.{re}-{re}\.metadix$", re = re),
                 format!(r"apertium-{re}-{re}\.post-{re}\.dix$", re = re),
                 format!(r"apertium-{re}\.post-{re}\.dix$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}\.postgen(?:\.dix)?$", re = re),
+                format!(r"apertium-{re}\.{re}\.postgen(?:\.dix)?$", re = re),
                 format!(r"apertium-{re}-{re}\.{re}-{re}\.rlx$", re = re),
                 format!(r"apertium-{re}\.{re}\.rlx$", re = re),
-                format!(r"apertium-{re}-{re}\.{re}-{re}\.t\dx$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.t1x$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.t2x$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.t3x$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.t4x$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.rtx$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.lrx$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.lsx$", re = re),
+                format!(r"apertium-{re}\.{re}\.lsx$", re = re),
+                format!(r"apertium-{re}-{re}\.{re}-{re}\.arx$", re = re),
                 format!(r"apertium-{re}\.{re}\.lexc$", re = re),
                 format!(r"apertium-{re}-{re}\.{re}\.twol$", re = re),
                 format!(r"apertium-{re}\.{re}\.twol$", re = re),
@@ -99,12 +128,19 @@ pub fn get_file_kind(file_name: &str) -> Option<FileKind> {
         1 => Some(FileKind::Bidix),
         2 | 3 => Some(FileKind::MetaMonodix),
         4 => Some(FileKind::MetaBidix),
-        5 | 6 => Some(FileKind::Postdix),
-        7 | 8 => Some(FileKind::Rlx),
-        9 => Some(FileKind::Transfer),
-        10 => Some(FileKind::Lexc),
-        11 | 12 => Some(FileKind::Twol),
-        13 => Some(FileKind::Lexd),
+        5..=8 => Some(FileKind::Postdix),
+        9 | 10 => Some(FileKind::Rlx),
+        11 => Some(FileKind::T1x),
+        12 => Some(FileKind::T2x),
+        13 => Some(FileKind::T3x),
+        14 => Some(FileKind::T4x),
+        15 => Some(FileKind::Rtx),
+        16 => Some(FileKind::Lrx),
+        17 | 18 => Some(FileKind::Lsx),
+        19 => Some(FileKind::Arx),
+        20 => Some(FileKind::Lexc),
+        21 | 22 => Some(FileKind::Twol),
+        23 => Some(FileKind::Lexd),
         _ => None,
     })
 }
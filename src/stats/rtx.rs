@@ -0,0 +1,152 @@
+use rocket_contrib::{json, json::JsonValue};
+use slog::Logger;
+
+use crate::{models::StatKind, stats::StatsError};
+
+fn make_parse_error(line_number: usize, error: &str) -> StatsError {
+    StatsError::Rtx(format!("Unable to parse L{}: {}", line_number, error))
+}
+
+// Splits the body into `;` terminated statements along with their starting line, dropping comments
+fn get_statements(body: &str) -> Result<Vec<(usize, String)>, StatsError> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut start_line_number = None;
+    let mut line_number = 1;
+    let mut depth = 0;
+    let mut in_quotes = false;
+
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if !c.is_whitespace() && start_line_number.is_none() {
+            start_line_number = Some(line_number);
+        }
+        match c {
+            '\n' => {
+                line_number += 1;
+                current.push(c);
+            },
+            '\\' => {
+                current.push(c);
+                if let Some(escaped) = chars.next() {
+                    if escaped == '\n' {
+                        line_number += 1;
+                    }
+                    current.push(escaped);
+                }
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            },
+            _ if in_quotes => current.push(c),
+            '!' => {
+                if current.trim().is_empty() {
+                    start_line_number = None;
+                }
+                if chars.by_ref().any(|c| c == '\n') {
+                    line_number += 1;
+                    current.push('\n');
+                }
+            },
+            '(' | '[' | '{' => {
+                depth += 1;
+                current.push(c);
+            },
+            ')' | ']' | '}' => {
+                if depth == 0 {
+                    return Err(make_parse_error(line_number, &format!("unexpected {}", c)));
+                }
+                depth -= 1;
+                current.push(c);
+            },
+            ';' if depth == 0 => {
+                statements.push((start_line_number.unwrap_or(line_number), current.trim().to_string()));
+                current.clear();
+                start_line_number = None;
+            },
+            _ => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        Err(make_parse_error(line_number, "unterminated string"))
+    } else if depth != 0 {
+        Err(make_parse_error(line_number, "unclosed bracket"))
+    } else if !current.trim().is_empty() {
+        Err(make_parse_error(start_line_number.unwrap_or(line_number), "missing ;"))
+    } else {
+        Ok(statements)
+    }
+}
+
+// Byte offsets of `needle` outside of strings and brackets
+fn find_top_level(statement: &str, needle: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (i, c) in statement.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if in_quotes => (),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ if depth == 0 && statement[i..].starts_with(needle) => offsets.push(i),
+            _ => (),
+        }
+    }
+
+    offsets
+}
+
+pub fn get_stats(_logger: &Logger, body: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
+    let mut rule_count = 0;
+    let mut output_pattern_count = 0;
+    let mut attribute_count = 0;
+    let mut macro_count = 0;
+
+    for (line_number, statement) in get_statements(body)? {
+        if statement.is_empty() {
+            continue;
+        }
+
+        let colon = find_top_level(&statement, ":").first().copied();
+        if !find_top_level(&statement, "->").is_empty() {
+            // Each `|` separated alternative compiles to its own rule
+            rule_count += find_top_level(&statement, "|").len() + 1;
+        } else if find_top_level(&statement, ">")
+            .first()
+            .map_or(false, |&i| colon.map_or(true, |colon| i < colon))
+        {
+            // Tag rewrite rules, e.g. `number > num : sg sg, pl pl`
+        } else if let Some(&equals) = find_top_level(&statement, "=").first() {
+            if statement[..equals].trim().is_empty() {
+                return Err(make_parse_error(line_number, "missing attribute category name"));
+            }
+            attribute_count += 1;
+        } else if let Some(colon) = colon {
+            // Macros are conditional blocks, output patterns are tag sequences
+            if statement[colon + 1..].trim_start().starts_with('(') {
+                macro_count += 1;
+            } else {
+                output_pattern_count += 1;
+            }
+        } else {
+            return Err(make_parse_error(line_number, "unrecognized statement"));
+        }
+    }
+
+    Ok(vec![
+        (StatKind::Rules, json!(rule_count)),
+        (StatKind::OutputPatterns, json!(output_pattern_count)),
+        (StatKind::Attributes, json!(attribute_count)),
+        (StatKind::Macros, json!(macro_count)),
+    ])
+}
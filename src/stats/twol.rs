@@ -0,0 +1,134 @@
+use std::{collections::BTreeSet, mem};
+
+use rocket_contrib::{json, json::JsonValue};
+use slog::Logger;
+
+use crate::{models::StatKind, stats::StatsError};
+
+const SECTIONS: &[&str] = &[
+    "Alphabet",
+    "Diacritics",
+    "Rule-variables",
+    "Sets",
+    "Definitions",
+    "Rules",
+];
+
+fn make_parse_error(line_number: usize, error: &str) -> StatsError {
+    StatsError::Twol(format!("Unable to parse L{}: {}", line_number, error))
+}
+
+// Splits the body into tokens along with their line, treating `;` and `=` as tokens of their own
+fn get_tokens(body: &str) -> Result<Vec<(usize, String)>, StatsError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut line_number = 1;
+
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {
+                current.push(c);
+                match chars.next() {
+                    Some('\n') | None => return Err(make_parse_error(line_number, "dangling %")),
+                    Some(escaped) => current.push(escaped),
+                }
+            },
+            '"' => {
+                current.push(c);
+                loop {
+                    match chars.next() {
+                        Some('"') => {
+                            current.push('"');
+                            break;
+                        },
+                        Some('\n') | None => return Err(make_parse_error(line_number, "unterminated string")),
+                        Some(c) => current.push(c),
+                    }
+                }
+            },
+            '!' => {
+                while chars.peek().map_or(false, |&c| c != '\n') {
+                    chars.next();
+                }
+            },
+            ';' | '=' => {
+                if !current.is_empty() {
+                    tokens.push((line_number, mem::take(&mut current)));
+                }
+                tokens.push((line_number, c.to_string()));
+            },
+            _ if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push((line_number, mem::take(&mut current)));
+                }
+                if c == '\n' {
+                    line_number += 1;
+                }
+            },
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((line_number, current));
+    }
+
+    Ok(tokens)
+}
+
+pub fn get_stats(_logger: &Logger, body: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
+    let mut rule_count = 0;
+    let mut set_count = 0;
+    let mut definition_count = 0;
+    let mut alphabet: BTreeSet<String> = BTreeSet::new();
+    let mut diacritics: BTreeSet<String> = BTreeSet::new();
+
+    let mut current_section: Option<&str> = None;
+    // Tokens of the current `;` terminated statement
+    let mut statement: Vec<(usize, String)> = Vec::new();
+
+    for (line_number, token) in get_tokens(body)? {
+        if statement.is_empty() && SECTIONS.contains(&token.as_str()) {
+            current_section = SECTIONS.iter().copied().find(|&section| section == token);
+            continue;
+        }
+
+        match current_section {
+            None => return Err(make_parse_error(line_number, "expected section header")),
+            Some("Alphabet") if token != ";" => {
+                alphabet.insert(token);
+            },
+            Some("Diacritics") if token != ";" => {
+                diacritics.insert(token);
+            },
+            // Rules are named by a quoted string, and their contexts are also `;` terminated
+            Some("Rules") if statement.is_empty() && token.starts_with('"') => rule_count += 1,
+            Some("Sets") | Some("Definitions") if token == ";" => {
+                if statement.get(1).map(|(_, token)| token.as_str()) != Some("=") {
+                    let line_number = statement.first().map_or(line_number, |(line_number, _)| *line_number);
+                    return Err(make_parse_error(line_number, "expected NAME = ..."));
+                }
+                if current_section == Some("Sets") {
+                    set_count += 1;
+                } else {
+                    definition_count += 1;
+                }
+                statement.clear();
+            },
+            Some(_) if token == ";" => statement.clear(),
+            Some(_) => statement.push((line_number, token)),
+        }
+    }
+
+    if let Some((line_number, _)) = statement.first() {
+        return Err(make_parse_error(*line_number, "missing ;"));
+    }
+
+    Ok(vec![
+        (StatKind::Rules, json!(rule_count)),
+        (StatKind::AlphabetSize, json!(alphabet.len())),
+        (StatKind::Sets, json!(set_count)),
+        (StatKind::Definitions, json!(definition_count)),
+        (StatKind::Diacritics, json!(diacritics.len())),
+    ])
+}
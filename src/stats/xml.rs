@@ -1,24 +1,358 @@
-use std::str;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::Hash,
+    str,
+};
 
 use quick_xml::{
-    events::{attributes::Attribute, Event},
+    events::{BytesStart, Event},
     Reader,
 };
 use rocket_contrib::{json, json::JsonValue};
 
 use crate::{models::StatKind, stats::StatsError};
 
+fn get_attribute(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key == key)
+        .map(|a| String::from_utf8_lossy(&a.value).into_owned())
+}
+
+fn is_ignored(e: &BytesStart) -> bool {
+    get_attribute(e, b"i").as_deref() == Some("yes")
+}
+
+// Language variants (e.g. Valencian or Nynorsk) keyed by attribute, then variant name
+type Variants = BTreeMap<&'static str, BTreeMap<String, usize>>;
+
+fn record_variants(e: &BytesStart, variants: &mut Variants) {
+    for attribute in &["v", "vl", "vr"] {
+        if let Some(variant) = get_attribute(e, attribute.as_bytes()) {
+            *variants.entry(*attribute).or_default().entry(variant).or_insert(0) += 1;
+        }
+    }
+}
+
+// Comments and authorship don't make otherwise identical entries distinct
+fn get_entry_attributes(e: &BytesStart) -> Vec<(String, String)> {
+    let mut attributes = e
+        .attributes()
+        .filter_map(|a| a.ok())
+        .filter(|a| a.key != b"c" && a.key != b"a")
+        .map(|a| {
+            (
+                String::from_utf8_lossy(a.key).into_owned(),
+                String::from_utf8_lossy(&a.value).into_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    attributes.sort();
+    attributes
+}
+
+struct LineCounter<'a> {
+    body: &'a [u8],
+    position: usize,
+    line: usize,
+}
+
+impl<'a> LineCounter<'a> {
+    fn new(body: &'a str) -> Self {
+        LineCounter {
+            body: body.as_bytes(),
+            position: 0,
+            line: 1,
+        }
+    }
+
+    // Positions must be non-decreasing, which holds for a single pass of the reader
+    fn get_line(&mut self, position: usize) -> usize {
+        let position = position.min(self.body.len());
+        self.line += self.body[self.position..position]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        self.position = position;
+        self.line
+    }
+}
+
+// Entries are compared on their markup, with whitespace collapsed so layout doesn't matter
+fn record_content(content: &mut String, event: &Event) {
+    match event {
+        Event::Start(e) | Event::Empty(e) => {
+            content.push_str(&format!("<{}", String::from_utf8_lossy(e.name())));
+            for (key, value) in get_entry_attributes(e) {
+                content.push_str(&format!(" {}=\"{}\"", key, value));
+            }
+            content.push_str(if let Event::Empty(_) = event { "/>" } else { ">" });
+        },
+        Event::End(e) => content.push_str(&format!("</{}>", String::from_utf8_lossy(e.name()))),
+        Event::Text(e) => {
+            if let Ok(text) = e.unescaped() {
+                content.push_str(
+                    &String::from_utf8_lossy(&text)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+            }
+        },
+        _ => (),
+    }
+}
+
+// Exact duplicates share their full content, near duplicates only their lemmas (and paradigms in monodix)
+struct Duplicates<K, N> {
+    exact: HashMap<K, (JsonValue, Vec<usize>)>,
+    near: HashMap<N, (JsonValue, Vec<usize>, HashSet<K>)>,
+}
+
+impl<K: Clone + Eq + Hash, N: Eq + Hash> Duplicates<K, N> {
+    fn new() -> Self {
+        Duplicates {
+            exact: HashMap::new(),
+            near: HashMap::new(),
+        }
+    }
+
+    fn record(&mut self, key: K, near_key: N, line: usize, describe: impl Fn() -> JsonValue) {
+        let exact = self
+            .exact
+            .entry(key.clone())
+            .or_insert_with(|| (describe(), Vec::new()));
+        exact.1.push(line);
+        let near = self
+            .near
+            .entry(near_key)
+            .or_insert_with(|| (describe(), Vec::new(), HashSet::new()));
+        near.1.push(line);
+        near.2.insert(key);
+    }
+
+    fn into_stats(self) -> Vec<(StatKind, JsonValue)> {
+        let exact = self.exact.into_iter().map(|(_, duplicate)| duplicate);
+        let near = self
+            .near
+            .into_iter()
+            .filter(|(_, (_, _, contents))| contents.len() > 1)
+            .map(|(_, (entry, lines, _))| (entry, lines));
+        vec![
+            (StatKind::DuplicateEntries, get_duplicates_json(exact)),
+            (StatKind::NearDuplicateEntries, get_duplicates_json(near)),
+        ]
+    }
+}
+
+fn get_duplicates_json(duplicates: impl Iterator<Item = (JsonValue, Vec<usize>)>) -> JsonValue {
+    let mut duplicates = duplicates.filter(|(_, lines)| lines.len() > 1).collect::<Vec<_>>();
+    duplicates.sort_by_key(|(_, lines)| lines[0]);
+    json!(duplicates
+        .into_iter()
+        .map(|(entry, lines)| json!({ "entry": entry, "lines": lines }))
+        .collect::<Vec<_>>())
+}
+
+#[derive(Default)]
+struct Symbols {
+    defined: BTreeSet<String>,
+    used: BTreeSet<String>,
+}
+
+impl Symbols {
+    fn record(&mut self, e: &BytesStart) {
+        match e.name() {
+            b"sdef" => self.defined.extend(get_attribute(e, b"n")),
+            b"s" => self.used.extend(get_attribute(e, b"n")),
+            _ => (),
+        }
+    }
+
+    fn into_stats(self) -> Vec<(StatKind, JsonValue)> {
+        vec![
+            (StatKind::Symbols, json!(self.defined.len())),
+            (
+                StatKind::UnusedSymbols,
+                json!(self.defined.difference(&self.used).collect::<Vec<_>>()),
+            ),
+            (
+                StatKind::UndefinedSymbols,
+                json!(self.used.difference(&self.defined).collect::<Vec<_>>()),
+            ),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct Translations {
+    left: BTreeMap<String, BTreeSet<String>>,
+    right: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Translations {
+    fn add(&mut self, left: &str, right: &str, restriction: Option<&str>) {
+        let (left, right) = (left.trim(), right.trim());
+        if left.is_empty() || right.is_empty() {
+            return;
+        }
+
+        if restriction != Some("RL") {
+            self.left.entry(left.to_string()).or_default().insert(right.to_string());
+        }
+        if restriction != Some("LR") {
+            self.right
+                .entry(right.to_string())
+                .or_default()
+                .insert(left.to_string());
+        }
+    }
+
+    fn get_ambiguous_count(translations: &BTreeMap<String, BTreeSet<String>>) -> usize {
+        translations.values().filter(|targets| targets.len() > 1).count()
+    }
+
+    fn get_fan_out(translations: &BTreeMap<String, BTreeSet<String>>) -> JsonValue {
+        let total = translations.values().map(BTreeSet::len).sum::<usize>();
+        let average = if translations.is_empty() {
+            0.0
+        } else {
+            total as f64 / translations.len() as f64
+        };
+        json!({
+            "max": translations.values().map(BTreeSet::len).max().unwrap_or(0),
+            "average": (average * 100.0).round() / 100.0,
+        })
+    }
+
+    fn into_stats(self) -> Vec<(StatKind, JsonValue)> {
+        vec![
+            (
+                StatKind::AmbiguousLeftLemmas,
+                json!(Translations::get_ambiguous_count(&self.left)),
+            ),
+            (
+                StatKind::AmbiguousRightLemmas,
+                json!(Translations::get_ambiguous_count(&self.right)),
+            ),
+            (StatKind::LeftFanOut, Translations::get_fan_out(&self.left)),
+            (StatKind::RightFanOut, Translations::get_fan_out(&self.right)),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct BidixEntry {
+    left: String,
+    right: String,
+    content: String,
+    restriction: Option<String>,
+    attributes: Vec<(String, String)>,
+    line: usize,
+    is_multiword: bool,
+}
+
 pub fn get_bidix_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
     let mut reader = Reader::from_str(&body);
     let mut buf = Vec::new();
 
     let mut e_count = 0;
     let mut in_section = false;
+    let mut symbols = Symbols::default();
+    let mut entries_by_direction: BTreeMap<&str, usize> =
+        vec![("bidirectional", 0), ("lr", 0), ("rl", 0), ("slr", 0), ("srl", 0)]
+            .into_iter()
+            .collect();
+    let mut translations = Translations::default();
+    let mut multiword_count = 0;
+    let mut ignored_count = 0;
+    let mut entries_by_variant = Variants::new();
+    let mut lines = LineCounter::new(body);
+    let mut duplicates = Duplicates::new();
+    let mut current_entry: Option<BidixEntry> = None;
+    let mut current_side: Option<Vec<u8>> = None;
 
     loop {
-        match reader.read_event(&mut buf) {
+        let event = reader.read_event(&mut buf);
+        if let (Some(entry), Ok(event)) = (current_entry.as_mut(), &event) {
+            record_content(&mut entry.content, event);
+        }
+        match event {
             Ok(Event::Start(ref e)) if e.name() == b"section" => in_section = true,
-            Ok(Event::Start(ref e)) if in_section && e.name() == b"e" => e_count += 1,
+            Ok(Event::Start(ref e)) if in_section && e.name() == b"e" && is_ignored(e) => ignored_count += 1,
+            Ok(Event::Start(ref e)) if in_section && e.name() == b"e" => {
+                e_count += 1;
+                record_variants(e, &mut entries_by_variant);
+
+                let direction = match get_attribute(e, b"r").as_deref() {
+                    Some("LR") => "lr",
+                    Some("RL") => "rl",
+                    _ => "bidirectional",
+                };
+                *entries_by_direction.get_mut(direction).unwrap() += 1;
+                for variant in &["slr", "srl"] {
+                    if get_attribute(e, variant.as_bytes()).is_some() {
+                        *entries_by_direction.get_mut(variant).unwrap() += 1;
+                    }
+                }
+
+                current_entry = Some(BidixEntry {
+                    restriction: get_attribute(e, b"r"),
+                    attributes: get_entry_attributes(e),
+                    line: lines.get_line(reader.buffer_position()),
+                    ..Default::default()
+                });
+            },
+            Ok(Event::Start(ref e)) if current_entry.is_some() && matches!(e.name(), b"l" | b"r" | b"i") => {
+                current_side = Some(e.name().to_vec())
+            },
+            Ok(Event::Empty(ref e)) if current_side.is_some() && e.name() == b"b" => {
+                let entry = current_entry.as_mut().unwrap();
+                entry.is_multiword = true;
+                match current_side.as_deref() {
+                    Some(b"l") => entry.left.push(' '),
+                    Some(b"r") => entry.right.push(' '),
+                    _ => {
+                        entry.left.push(' ');
+                        entry.right.push(' ');
+                    },
+                }
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if current_entry.is_some() && matches!(e.name(), b"b" | b"g" | b"j") =>
+            {
+                current_entry.as_mut().unwrap().is_multiword = true
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => symbols.record(e),
+            Ok(Event::Text(ref e)) if current_side.is_some() => {
+                let text = e.unescaped().map(|text| String::from_utf8_lossy(&text).into_owned());
+                let entry = current_entry.as_mut().unwrap();
+                match (current_side.as_deref(), text) {
+                    (Some(b"l"), Ok(text)) => entry.left.push_str(&text),
+                    (Some(b"r"), Ok(text)) => entry.right.push_str(&text),
+                    (_, Ok(text)) => {
+                        entry.left.push_str(&text);
+                        entry.right.push_str(&text);
+                    },
+                    (_, Err(_)) => (),
+                }
+            },
+            Ok(Event::End(ref e)) if matches!(e.name(), b"l" | b"r" | b"i") => current_side = None,
+            Ok(Event::End(ref e)) if e.name() == b"e" => {
+                if let Some(entry) = current_entry.take() {
+                    if entry.is_multiword {
+                        multiword_count += 1;
+                    }
+                    translations.add(&entry.left, &entry.right, entry.restriction.as_deref());
+                    let (left, right) = (entry.left.trim(), entry.right.trim());
+                    duplicates.record(
+                        (entry.attributes, entry.content),
+                        (left.to_string(), right.to_string()),
+                        entry.line,
+                        || json!({ "left": left, "right": right }),
+                    );
+                }
+            },
             Ok(Event::End(ref e)) if e.name() == b"section" => in_section = false,
             Ok(Event::Eof) => break,
             Err(e) => {
@@ -34,32 +368,141 @@ pub fn get_bidix_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, Jso
         buf.clear();
     }
 
-    Ok(vec![(StatKind::Entries, json!(e_count))])
+    let mut stats = vec![
+        (StatKind::Entries, json!(e_count)),
+        (StatKind::EntriesByDirection, json!(entries_by_direction)),
+        (StatKind::MultiwordEntries, json!(multiword_count)),
+        (StatKind::EntriesByVariant, json!(entries_by_variant)),
+        (StatKind::IgnoredEntries, json!(ignored_count)),
+    ];
+    stats.extend(duplicates.into_stats());
+    stats.extend(symbols.into_stats());
+    stats.extend(translations.into_stats());
+    Ok(stats)
+}
+
+// Paradigm names conventionally end with their part of speech, e.g. house__n
+fn get_paradigm_pos(paradigm: &str, paradigm_tags: &HashMap<String, String>) -> Option<String> {
+    match paradigm.rfind("__") {
+        Some(i) if i + 2 < paradigm.len() => Some(paradigm[i + 2..].to_string()),
+        _ => paradigm_tags.get(paradigm).cloned(),
+    }
+}
+
+#[derive(Default)]
+struct MonodixEntry {
+    lemma: Option<String>,
+    attributes: Vec<(String, String)>,
+    content: String,
+    line: usize,
+    is_regex: bool,
+    is_multiword: bool,
+    paradigms: Vec<String>,
+    first_tag: Option<String>,
 }
 
 pub fn get_monodix_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
     let mut reader = Reader::from_str(&body);
     let mut buf = Vec::new();
 
-    let mut stem_count = 0;
     let mut pardef_count = 0;
-    let mut in_section = false;
+    let mut regex_count = 0;
+    let mut multiword_count = 0;
+    let mut ignored_count = 0;
+    let mut entries_by_variant = Variants::new();
+    let mut lines = LineCounter::new(body);
+    let mut duplicates = Duplicates::new();
+    let mut current_section_type: Option<String> = None;
     let mut in_pardefs = false;
 
+    let mut entries_by_section_type: BTreeMap<String, usize> = BTreeMap::new();
+    let mut stems: Vec<(Vec<String>, Option<String>)> = Vec::new();
+    let mut current_entry: Option<MonodixEntry> = None;
+    let mut current_pardef: Option<String> = None;
+    let mut paradigm_tags: HashMap<String, String> = HashMap::new();
+    let mut defined_paradigms: BTreeSet<String> = BTreeSet::new();
+    let mut referenced_paradigms: BTreeSet<String> = BTreeSet::new();
+    let mut symbols = Symbols::default();
+
     loop {
-        match reader.read_event(&mut buf) {
-            Ok(Event::Start(ref e)) if e.name() == b"section" => in_section = true,
+        let event = reader.read_event(&mut buf);
+        if let (Some(entry), Ok(event)) = (current_entry.as_mut(), &event) {
+            record_content(&mut entry.content, event);
+        }
+        match event {
+            Ok(Event::Start(ref e)) if e.name() == b"section" => {
+                current_section_type = Some(get_attribute(e, b"type").unwrap_or_else(|| "standard".to_string()))
+            },
             Ok(Event::Start(ref e)) if e.name() == b"pardefs" => in_pardefs = true,
-            Ok(Event::Start(ref e)) if in_section && e.name() == b"e" => {
-                if e.attributes()
-                    .any(|a| a.ok().map_or(false, |Attribute { key, .. }| key == b"lm"))
-                {
-                    stem_count += 1
+            Ok(Event::Start(ref e)) if current_section_type.is_some() && e.name() == b"e" && is_ignored(e) => {
+                ignored_count += 1
+            },
+            Ok(Event::Start(ref e)) if current_section_type.is_some() && e.name() == b"e" => {
+                record_variants(e, &mut entries_by_variant);
+                current_entry = Some(MonodixEntry {
+                    lemma: get_attribute(e, b"lm"),
+                    attributes: get_entry_attributes(e),
+                    line: lines.get_line(reader.buffer_position()),
+                    ..Default::default()
+                });
+            },
+            Ok(Event::Start(ref e)) if current_entry.is_some() && e.name() == b"re" => {
+                current_entry.as_mut().unwrap().is_regex = true
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if current_entry.is_some() && matches!(e.name(), b"b" | b"g" | b"j") =>
+            {
+                current_entry.as_mut().unwrap().is_multiword = true
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name() == b"par" => {
+                if let Some(paradigm) = get_attribute(e, b"n") {
+                    if let Some(entry) = current_entry.as_mut() {
+                        entry.paradigms.push(paradigm.clone());
+                    }
+                    referenced_paradigms.insert(paradigm);
                 }
             },
-            Ok(Event::Start(ref e)) if in_pardefs && e.name() == b"pardef" => pardef_count += 1,
-            Ok(Event::End(ref e)) if e.name() == b"section" => in_section = false,
+            Ok(Event::Start(ref e)) if in_pardefs && e.name() == b"pardef" => {
+                pardef_count += 1;
+                current_pardef = get_attribute(e, b"n");
+                defined_paradigms.extend(current_pardef.clone());
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name() == b"s" => {
+                if let (Some(pardef), Some(tag)) = (&current_pardef, get_attribute(e, b"n")) {
+                    paradigm_tags.entry(pardef.clone()).or_insert(tag);
+                }
+                if let Some(entry) = current_entry.as_mut().filter(|entry| entry.first_tag.is_none()) {
+                    entry.first_tag = get_attribute(e, b"n");
+                }
+                symbols.record(e);
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name() == b"sdef" => symbols.record(e),
+            Ok(Event::End(ref e)) if e.name() == b"section" => current_section_type = None,
             Ok(Event::End(ref e)) if e.name() == b"pardefs" => in_pardefs = false,
+            Ok(Event::End(ref e)) if e.name() == b"e" => {
+                if let (Some(entry), Some(section_type)) = (current_entry.take(), &current_section_type) {
+                    *entries_by_section_type.entry(section_type.clone()).or_insert(0) += 1;
+                    if entry.is_multiword && !entry.is_regex {
+                        multiword_count += 1;
+                    }
+                    // Regular expressions and punctuation in non-standard sections aren't stems
+                    if entry.is_regex {
+                        regex_count += 1;
+                    } else if let Some(lemma) = entry.lemma {
+                        let paradigms = entry.paradigms.clone();
+                        duplicates.record(
+                            (entry.attributes, entry.content),
+                            (lemma.clone(), entry.paradigms.clone()),
+                            entry.line,
+                            || json!({ "lemma": lemma, "paradigms": paradigms }),
+                        );
+                        if section_type == "standard" {
+                            stems.push((entry.paradigms, entry.first_tag));
+                        }
+                    }
+                }
+            },
+            Ok(Event::End(ref e)) if e.name() == b"pardef" => current_pardef = None,
             Ok(Event::Eof) => break,
             Err(e) => {
                 return Err(StatsError::Xml(format!(
@@ -74,10 +517,42 @@ pub fn get_monodix_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, J
         buf.clear();
     }
 
-    Ok(vec![
-        (StatKind::Stems, json!(stem_count)),
+    let mut stems_by_pos: BTreeMap<String, usize> = BTreeMap::new();
+    let mut paradigm_usage: BTreeMap<&String, usize> = defined_paradigms.iter().map(|name| (name, 0)).collect();
+    for (paradigms, first_tag) in &stems {
+        // Stems without a paradigm usually spell out their tags inline
+        let pos = paradigms
+            .first()
+            .and_then(|paradigm| get_paradigm_pos(paradigm, &paradigm_tags))
+            .or_else(|| first_tag.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        *stems_by_pos.entry(pos).or_insert(0) += 1;
+        for paradigm in paradigms.iter().collect::<BTreeSet<_>>() {
+            if let Some(count) = paradigm_usage.get_mut(paradigm) {
+                *count += 1;
+            }
+        }
+    }
+
+    let unused_paradigms = defined_paradigms.difference(&referenced_paradigms).collect::<Vec<_>>();
+    let undefined_paradigms = referenced_paradigms.difference(&defined_paradigms).collect::<Vec<_>>();
+
+    let mut stats = vec![
+        (StatKind::Stems, json!(stems.len())),
         (StatKind::Paradigms, json!(pardef_count)),
-    ])
+        (StatKind::StemsByPos, json!(stems_by_pos)),
+        (StatKind::ParadigmUsage, json!(paradigm_usage)),
+        (StatKind::UnusedParadigms, json!(unused_paradigms)),
+        (StatKind::UndefinedParadigms, json!(undefined_paradigms)),
+        (StatKind::EntriesBySectionType, json!(entries_by_section_type)),
+        (StatKind::RegexEntries, json!(regex_count)),
+        (StatKind::MultiwordEntries, json!(multiword_count)),
+        (StatKind::EntriesByVariant, json!(entries_by_variant)),
+        (StatKind::IgnoredEntries, json!(ignored_count)),
+    ];
+    stats.extend(duplicates.into_stats());
+    stats.extend(symbols.into_stats());
+    Ok(stats)
 }
 
 pub fn get_transfer_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
@@ -86,11 +561,44 @@ pub fn get_transfer_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind,
 
     let mut rule_count = 0;
     let mut macro_count = 0;
+    let mut cat_count = 0;
+    let mut attr_count = 0;
+    let mut var_count = 0;
+    let mut list_count = 0;
+
+    let mut current_pattern_length: Option<usize> = None;
+    let mut pattern_lengths: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut defined_macros: BTreeSet<String> = BTreeSet::new();
+    let mut called_macros: BTreeSet<String> = BTreeSet::new();
 
     loop {
         match reader.read_event(&mut buf) {
-            Ok(Event::Start(ref e)) if e.name() == b"rule" => rule_count += 1,
-            Ok(Event::Start(ref e)) if e.name() == b"def-macro" => macro_count += 1,
+            Ok(Event::Start(ref e)) if e.name() == b"rule" => {
+                rule_count += 1;
+                current_pattern_length = Some(0);
+            },
+            Ok(Event::Start(ref e)) if e.name() == b"def-macro" => {
+                macro_count += 1;
+                defined_macros.extend(get_attribute(e, b"n"));
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => match e.name() {
+                b"def-cat" => cat_count += 1,
+                b"def-attr" => attr_count += 1,
+                b"def-var" => var_count += 1,
+                b"def-list" => list_count += 1,
+                b"pattern-item" => {
+                    if let Some(length) = current_pattern_length.as_mut() {
+                        *length += 1;
+                    }
+                },
+                b"call-macro" => called_macros.extend(get_attribute(e, b"n")),
+                _ => (),
+            },
+            Ok(Event::End(ref e)) if e.name() == b"rule" => {
+                if let Some(length) = current_pattern_length.take() {
+                    *pattern_lengths.entry(length).or_insert(0) += 1;
+                }
+            },
             Ok(Event::Eof) => break,
             Err(e) => {
                 return Err(StatsError::Xml(format!(
@@ -108,5 +616,215 @@ pub fn get_transfer_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind,
     Ok(vec![
         (StatKind::Rules, json!(rule_count)),
         (StatKind::Macros, json!(macro_count)),
+        (StatKind::Categories, json!(cat_count)),
+        (StatKind::Attributes, json!(attr_count)),
+        (StatKind::Variables, json!(var_count)),
+        (StatKind::Lists, json!(list_count)),
+        (StatKind::RulePatternLengths, json!(pattern_lengths)),
+        (
+            StatKind::UnusedMacros,
+            json!(defined_macros.difference(&called_macros).collect::<Vec<_>>()),
+        ),
+    ])
+}
+
+pub fn get_lrx_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
+    let mut reader = Reader::from_str(&body);
+    let mut buf = Vec::new();
+
+    let mut rule_count = 0;
+    let mut rule_weights: BTreeMap<String, usize> = BTreeMap::new();
+    let mut covered_lemmas: BTreeSet<String> = BTreeSet::new();
+    let mut current_match_lemma: Option<Option<String>> = None;
+
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name() == b"rule" => {
+                rule_count += 1;
+                // Rules without an explicit weight are compiled with a weight of 1
+                let weight = get_attribute(e, b"weight")
+                    .and_then(|weight| weight.trim().parse::<f64>().ok())
+                    .unwrap_or(1.0);
+                *rule_weights.entry(weight.to_string()).or_insert(0) += 1;
+            },
+            Ok(Event::Start(ref e)) if e.name() == b"match" => current_match_lemma = Some(get_attribute(e, b"lemma")),
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if matches!(e.name(), b"select" | b"remove") => {
+                if let Some(Some(lemma)) = &current_match_lemma {
+                    covered_lemmas.insert(lemma.clone());
+                }
+            },
+            Ok(Event::End(ref e)) if e.name() == b"match" => current_match_lemma = None,
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(StatsError::Xml(format!(
+                    "Error at position {} in {}: {:?}",
+                    reader.buffer_position(),
+                    file_path,
+                    e
+                )));
+            },
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(vec![
+        (StatKind::Rules, json!(rule_count)),
+        (StatKind::CoveredLemmas, json!(covered_lemmas.len())),
+        (StatKind::RuleWeights, json!(rule_weights)),
+    ])
+}
+
+#[derive(Default)]
+struct LsxEntry {
+    token_count: usize,
+    paradigms: Vec<String>,
+}
+
+// Paradigms stand in for whole phrases, so they contribute their longest alternative
+fn get_lsx_entry_length(
+    entry: &LsxEntry,
+    pardef_entries: &HashMap<String, Vec<LsxEntry>>,
+    visited: &mut Vec<String>,
+) -> usize {
+    entry.token_count
+        + entry
+            .paradigms
+            .iter()
+            .map(|paradigm| {
+                if visited.contains(paradigm) {
+                    return 0;
+                }
+                visited.push(paradigm.clone());
+                let length = pardef_entries.get(paradigm).map_or(0, |entries| {
+                    entries
+                        .iter()
+                        .map(|entry| get_lsx_entry_length(entry, pardef_entries, visited))
+                        .max()
+                        .unwrap_or(0)
+                });
+                visited.pop();
+                length
+            })
+            .sum::<usize>()
+}
+
+pub fn get_lsx_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
+    let mut reader = Reader::from_str(&body);
+    let mut buf = Vec::new();
+
+    let mut in_section = false;
+    let mut current_pardef: Option<String> = None;
+    let mut current_entry: Option<LsxEntry> = None;
+    let mut in_right = false;
+    let mut entries: Vec<LsxEntry> = Vec::new();
+    let mut pardef_entries: HashMap<String, Vec<LsxEntry>> = HashMap::new();
+
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name() == b"section" => in_section = true,
+            Ok(Event::Start(ref e)) if e.name() == b"pardef" => current_pardef = get_attribute(e, b"n"),
+            Ok(Event::Start(ref e)) if e.name() == b"e" && !is_ignored(e) => current_entry = Some(LsxEntry::default()),
+            Ok(Event::Start(ref e)) if current_entry.is_some() && e.name() == b"r" => in_right = true,
+            Ok(Event::End(ref e)) if e.name() == b"r" => in_right = false,
+            // Every token of a separable entry is terminated by <j/>, counted on the left side of <p> pairs only
+            Ok(Event::Empty(ref e)) if current_entry.is_some() && !in_right && e.name() == b"j" => {
+                current_entry.as_mut().unwrap().token_count += 1
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if current_entry.is_some() && e.name() == b"par" => {
+                let entry = current_entry.as_mut().unwrap();
+                entry.paradigms.extend(get_attribute(e, b"n"));
+            },
+            Ok(Event::End(ref e)) if e.name() == b"e" => {
+                if let Some(entry) = current_entry.take() {
+                    if let Some(pardef) = &current_pardef {
+                        pardef_entries.entry(pardef.clone()).or_default().push(entry);
+                    } else if in_section {
+                        entries.push(entry);
+                    }
+                }
+            },
+            Ok(Event::End(ref e)) if e.name() == b"section" => in_section = false,
+            Ok(Event::End(ref e)) if e.name() == b"pardef" => current_pardef = None,
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(StatsError::Xml(format!(
+                    "Error at position {} in {}: {:?}",
+                    reader.buffer_position(),
+                    file_path,
+                    e
+                )));
+            },
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    let longest_entry = entries
+        .iter()
+        .map(|entry| get_lsx_entry_length(entry, &pardef_entries, &mut Vec::new()))
+        .max()
+        .unwrap_or(0);
+
+    Ok(vec![
+        (StatKind::SeparableEntries, json!(entries.len())),
+        (StatKind::LongestEntry, json!(longest_entry)),
+    ])
+}
+
+pub fn get_arx_stats(body: &str, file_path: &str) -> Result<Vec<(StatKind, JsonValue)>, StatsError> {
+    let mut reader = Reader::from_str(&body);
+    let mut buf = Vec::new();
+
+    let mut markable_count = 0;
+    let mut parameter_count = 0;
+    // Parameters are the direct children of <section-parameters>, whatever their element name
+    let mut parameters_depth: Option<usize> = None;
+
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name() == b"section-parameters" => parameters_depth = Some(0),
+            Ok(Event::End(ref e)) if e.name() == b"section-parameters" => parameters_depth = None,
+            Ok(Event::Start(ref e)) => {
+                if e.name() == b"markable" {
+                    markable_count += 1;
+                }
+                if let Some(depth) = parameters_depth.as_mut() {
+                    if *depth == 0 {
+                        parameter_count += 1;
+                    }
+                    *depth += 1;
+                }
+            },
+            Ok(Event::Empty(ref e)) => {
+                if e.name() == b"markable" {
+                    markable_count += 1;
+                }
+                if parameters_depth == Some(0) {
+                    parameter_count += 1;
+                }
+            },
+            Ok(Event::End(_)) => {
+                if let Some(depth) = parameters_depth.as_mut() {
+                    *depth = depth.saturating_sub(1);
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(StatsError::Xml(format!(
+                    "Error at position {} in {}: {:?}",
+                    reader.buffer_position(),
+                    file_path,
+                    e
+                )));
+            },
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(vec![
+        (StatKind::Markables, json!(markable_count)),
+        (StatKind::Parameters, json!(parameter_count)),
     ])
 }
@@ -25,11 +25,43 @@ pub fn setup_database() -> tempfile::NamedTempFile {
     db_file
 }
 
+pub fn run_git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Test Author")
+        .env("GIT_AUTHOR_EMAIL", "author@example.com")
+        .env("GIT_COMMITTER_NAME", "Test Committer")
+        .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+        .stdout(Stdio::null())
+        .status()
+        .expect("successful git invocation");
+    assert!(status.success(), "git {:?}", args);
+}
+
+pub fn commit_files(repo_dir: &Path, files: &[(&str, &str)], message: &str) {
+    for (path, contents) in files {
+        let file_path = repo_dir.join(path);
+        fs::create_dir_all(file_path.parent().expect("valid parent directory")).expect("valid directory creation");
+        fs::write(file_path, contents).expect("valid file write");
+    }
+
+    run_git(repo_dir, &["add", "--all"]);
+    run_git(repo_dir, &["commit", "--quiet", "--message", message]);
+}
+
 macro_rules! run_test {
     (| $client:ident | $block:expr) => {{
         let db_file = $crate::tests::common::setup_database();
         let db_path = db_file.path().to_str().expect("valid database path");
-        let $client = Client::new(service(db_path.into(), None, None)).expect("valid rocket instance");
+        let mirrors_dir = TempDir::new().expect("valid mirrors directory");
+        let source = Arc::new(GitHubProvider::new(
+            mirrors_dir.path().to_path_buf(),
+            None,
+            GITHUB_GRAPHQL_API_ENDPOINT.to_string(),
+        ));
+        let $client = Client::new(service(db_path.into(), source)).expect("valid rocket instance");
         $block
     }};
 }
@@ -43,6 +75,7 @@ macro_rules! run_test_with_github_auth {
 
         let db_file = $crate::tests::common::setup_database();
         let db_path = db_file.path().to_str().expect("valid database path");
+        let mirrors_dir = TempDir::new().expect("valid mirrors directory");
 
         let server = MockServer::start();
         for (i, mut listing) in PACKAGE_LISTING.clone().into_iter().enumerate() {
@@ -71,16 +104,23 @@ macro_rules! run_test_with_github_auth {
             });
         }
 
-        let $client = Client::new(service(
-            db_path.into(),
-            Some(&github_auth_token),
-            Some(&server.base_url()),
-        ))
-        .expect("valid rocket instance");
+        let source = Arc::new(GitHubProvider::new(
+            mirrors_dir.path().to_path_buf(),
+            Some(github_auth_token.to_string()),
+            server.base_url(),
+        ));
+        let $client = Client::new(service(db_path.into(), source)).expect("valid rocket instance");
         $block
     }};
 }
 
+pub fn find_stat<'a>(stats: &'a [serde_json::Value], stat_kind: &str) -> &'a serde_json::Value {
+    stats
+        .iter()
+        .find(|entry| entry["stat_kind"] == stat_kind)
+        .unwrap_or_else(|| panic!("missing {} stat", stat_kind))
+}
+
 pub fn parse_response(mut response: LocalResponse) -> JsonValue {
     JsonValue(serde_json::from_str(&response.body_string().expect("non-empty body")).expect("valid JSON response"))
 }
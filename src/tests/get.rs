@@ -13,6 +13,18 @@ fn nonexistent_package_stats() {
     });
 }
 
+#[test]
+fn nonexistent_package_specific_stats() {
+    run_test!(|client| {
+        let response = client.get("/apertium-xxx/monodix").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = parse_response(response);
+        assert_eq!(body["name"], "apertium-xxx");
+        let error = body["error"].as_str().expect("error is string");
+        assert!(error.starts_with("Package not found"), "{}", error.to_string());
+    });
+}
+
 #[test]
 fn invalid_package_stats() {
     run_test!(|client| {
@@ -63,6 +75,23 @@ fn invalid_kind_package_stats() {
     });
 }
 
+#[test]
+fn transfer_kind_package_stats() {
+    run_test!(|client| {
+        let endpoint = format!("/{}/transfer", TEST_HFST_MODULE);
+        let response = client.get(endpoint).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = parse_response(response);
+        assert_eq!(
+            body,
+            json!({
+                "error": "Transfer files are split into t1x, t2x, t3x and t4x kinds",
+                "name": "apertium-kaz"
+            })
+        );
+    });
+}
+
 #[test]
 fn module_stats() {
     let module = format!("apertium-{}", TEST_HFST_MODULE);
@@ -147,11 +176,10 @@ fn hfst_pair_stats() {
                 assert!(
                     stats
                         .iter()
-                        .map(|entry| (
-                            entry["stat_kind"].as_str().expect("kind is string"),
-                            entry["value"].as_i64().expect("value is i64")
-                        ))
-                        .all(|(kind, value)| kind == "Macros" || value > 0),
+                        .filter_map(|entry| entry["value"]
+                            .as_i64()
+                            .map(|value| (entry["stat_kind"].as_str().expect("kind is string"), value)))
+                        .all(|(kind, value)| POSSIBLY_ZERO_STAT_KINDS.contains(&kind) || value > 0),
                     "{}",
                     body["stats"].to_string(),
                 );
@@ -182,11 +210,10 @@ fn lt_pair_stats() {
         assert!(
             stats
                 .iter()
-                .map(|entry| (
-                    entry["stat_kind"].as_str().expect("kind is string"),
-                    entry["value"].as_i64().expect("value is i64")
-                ))
-                .all(|(kind, value)| kind == "Macros" || value > 0),
+                .filter_map(|entry| entry["value"]
+                    .as_i64()
+                    .map(|value| (entry["stat_kind"].as_str().expect("kind is string"), value)))
+                .all(|(kind, value)| POSSIBLY_ZERO_STAT_KINDS.contains(&kind) || value > 0),
             "{}",
             body["stats"].to_string(),
         );
@@ -203,7 +230,7 @@ fn lt_pair_stats() {
 
 #[test]
 fn pair_specific_stats() {
-    let kinds = [("transfer", 12), ("bidix", 1)];
+    let kinds = [("t1x", 16), ("t2x", 16), ("t3x", 16), ("bidix", 14), ("lrx", 6)];
 
     for (kind, stat_count) in &kinds {
         let module = format!("apertium-{}", TEST_LT_PAIR);
@@ -254,7 +281,7 @@ fn lexd_module_stats() {
 
 #[test]
 fn module_specific_stats() {
-    let kinds = [("monodix", 2), ("rlx", 1), ("postdix", 1)];
+    let kinds = [("monodix", 16), ("rlx", 1), ("postdix", 14)];
 
     for (kind, stat_count) in &kinds {
         let module = format!("apertium-{}", TEST_LT_MODULE);
@@ -324,7 +351,7 @@ fn sync_package_stats() {
 
         assert_eq!(body["name"], module);
         let stats = body["stats"].as_array().expect("valid stats");
-        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.len(), 16);
     });
 }
 
@@ -0,0 +1,551 @@
+use self::common::*;
+use super::*;
+
+fn setup_package(root: &Path, name: &str) {
+    let repo_dir = root.join(name);
+    fs::create_dir_all(&repo_dir).expect("valid repository directory");
+    run_git(&repo_dir, &["init", "--quiet"]);
+    commit_files(
+        &repo_dir,
+        &[
+            ("apertium-eng.eng.dix", "<dictionary/>"),
+            ("dev/apertium-eng.eng.rlx", "DELIMITERS = \"<.>\" ;"),
+        ],
+        "Initial commit",
+    );
+    commit_files(
+        &repo_dir,
+        &[("apertium-eng.eng.dix", "<dictionary></dictionary>")],
+        "Update monodix",
+    );
+}
+
+fn check_listing(source: &dyn SourceProvider) {
+    let logger = Logger::root(slog::Discard, o!());
+
+    let mut files = RUNTIME
+        .block_on(source.list_files(&logger, "apertium-eng", "HEAD", true))
+        .expect("valid listing");
+    files.sort_by_key(|file| file.path.clone());
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].path, "apertium-eng.eng.dix");
+    assert_eq!(files[0].size, 25);
+    assert_eq!(files[0].revision, 2);
+    assert_eq!(files[0].last_author, "Test Author");
+    assert_eq!(files[0].sha.len(), 40);
+    assert_eq!(files[0].blob.len(), 40);
+    assert_eq!(files[1].path, "dev/apertium-eng.eng.rlx");
+    assert_eq!(files[1].revision, 1);
+    assert_ne!(files[0].sha, files[1].sha);
+
+    let body = RUNTIME
+        .block_on(source.fetch_file(&logger, "apertium-eng", &files[0]))
+        .expect("valid body");
+    assert_eq!(body, "<dictionary></dictionary>");
+
+    let files = RUNTIME
+        .block_on(source.list_files(&logger, "apertium-eng", "HEAD", false))
+        .expect("valid listing");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "apertium-eng.eng.dix");
+
+    let default_branch = RUNTIME
+        .block_on(source.get_default_branch(&logger, "apertium-eng"))
+        .expect("valid default branch");
+    let files = RUNTIME
+        .block_on(source.list_files(
+            &logger,
+            "apertium-eng",
+            &format!("refs/heads/{}", default_branch),
+            false,
+        ))
+        .expect("valid listing");
+    assert_eq!(files.len(), 1);
+
+    let (packages, _) = RUNTIME.block_on(source.list_packages(&logger)).expect("valid packages");
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].name, "apertium-eng");
+    assert_eq!(packages[0].default_branch, Some(default_branch));
+    let last_commit = packages[0].last_commit.as_ref().expect("valid last commit");
+    assert_eq!(last_commit.sha, files[0].sha);
+    assert_eq!(last_commit.message, "Update monodix");
+}
+
+#[test]
+fn git_source_listing() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+
+    let remotes_dir = TempDir::new().expect("valid remotes directory");
+    let checkout_path = checkouts_dir.path().join("apertium-eng");
+    run_git(
+        remotes_dir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            checkout_path.to_str().expect("valid checkout path"),
+            "apertium-eng.git",
+        ],
+    );
+
+    let mirrors_dir = TempDir::new().expect("valid mirrors directory");
+    let source = GitProvider::new(
+        remotes_dir.path().to_str().expect("valid remotes path").to_string(),
+        mirrors_dir.path().to_path_buf(),
+    );
+    check_listing(&source);
+}
+
+#[test]
+fn local_source_listing() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+    check_listing(&source);
+}
+
+#[test]
+fn merged_file_listing() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    let repo_dir = checkouts_dir.path().join("apertium-eng");
+    fs::create_dir_all(&repo_dir).expect("valid repository directory");
+    run_git(&repo_dir, &["init", "--quiet"]);
+    commit_files(
+        &repo_dir,
+        &[("apertium-eng.eng.rlx", "a\nb\nc\nd\ne\n")],
+        "Initial commit",
+    );
+    run_git(&repo_dir, &["checkout", "--quiet", "-b", "side"]);
+    commit_files(
+        &repo_dir,
+        &[("apertium-eng.eng.rlx", "A\nb\nc\nd\ne\n")],
+        "Update first line",
+    );
+    run_git(&repo_dir, &["checkout", "--quiet", "-"]);
+    commit_files(
+        &repo_dir,
+        &[("apertium-eng.eng.rlx", "a\nb\nc\nd\nE\n")],
+        "Update last line",
+    );
+    run_git(&repo_dir, &["merge", "--quiet", "--no-ff", "-m", "Merge side", "side"]);
+    commit_files(&repo_dir, &[("README", "Unrecognized")], "Add readme");
+
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+    let logger = Logger::root(slog::Discard, o!());
+    let files = RUNTIME
+        .block_on(source.list_files(&logger, "apertium-eng", "HEAD", false))
+        .expect("valid listing");
+    // Only recognized files are listed, and the merge is the last change to the rlx
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "apertium-eng.eng.rlx");
+    assert_eq!(files[0].revision, 4);
+    assert_eq!(files[0].last_author, "Test Author");
+
+    let body = RUNTIME
+        .block_on(source.fetch_file(&logger, "apertium-eng", &files[0]))
+        .expect("valid body");
+    assert_eq!(body, "A\nb\nc\nd\nE\n");
+}
+
+#[test]
+fn resume_unfinished_tasks() {
+    use crate::{
+        models::{NewTask, Task as DbTask, TaskStatus},
+        schema::tasks,
+    };
+    use chrono::NaiveDate;
+
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let logger = Logger::root(slog::Discard, o!());
+    let branch = RUNTIME
+        .block_on(source.get_default_branch(&logger, "apertium-eng"))
+        .expect("valid default branch");
+    let file = RUNTIME
+        .block_on(source.list_files(&logger, "apertium-eng", &format!("refs/heads/{}", branch), false))
+        .expect("valid listing")
+        .remove(0);
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let conn = SqliteConnection::establish(db_path).expect("valid database connection");
+    let created = NaiveDate::from_ymd(2021, 1, 1).and_hms(0, 0, 0);
+    let make_task = |path: &str, status| NewTask {
+        name: "apertium-eng".to_string(),
+        branch: branch.clone(),
+        path: path.to_string(),
+        file_kind: FileKind::Monodix,
+        revision: file.revision,
+        sha: file.sha.clone(),
+        blob: file.blob.clone(),
+        size: file.size,
+        last_author: file.last_author.clone(),
+        last_changed: file.last_changed,
+        status,
+        created,
+    };
+    diesel::insert_into(tasks::table)
+        .values(&vec![
+            make_task(&file.path, TaskStatus::Succeeded),
+            make_task(&file.path, TaskStatus::Running),
+            make_task("apertium-eng.eng.old.dix", TaskStatus::Failed),
+        ])
+        .execute(&conn)
+        .expect("valid task insertion");
+    diesel::update(tasks::table.filter(tasks::status.ne(TaskStatus::Running)))
+        .set(tasks::completed.eq(Some(created)))
+        .execute(&conn)
+        .expect("valid task update");
+
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+    wait_for_ok(&client, "/apertium-eng/monodix", |response| {
+        let body = parse_response(response);
+        if body["in_progress"].as_array().expect("valid in_progress").is_empty() {
+            let stats = body["stats"].as_array().expect("valid stats");
+            assert_eq!(stats.len(), 16);
+            for stat in stats {
+                assert_eq!(stat["requested"], created.format("%FT%T").to_string());
+            }
+            true
+        } else {
+            false
+        }
+    });
+
+    // Aged out tasks are pruned unless they are the latest failure of their file
+    let db_tasks = tasks::table
+        .order(tasks::id)
+        .load::<DbTask>(&conn)
+        .expect("valid tasks");
+    assert_eq!(db_tasks.len(), 2);
+    assert_eq!(db_tasks[0].path, file.path);
+    assert_eq!(db_tasks[0].status, TaskStatus::Succeeded);
+    assert!(db_tasks[0].started.is_some());
+    assert!(db_tasks[0].completed.expect("valid completed") > created);
+    assert_eq!(db_tasks[1].path, "apertium-eng.eng.old.dix");
+    assert_eq!(db_tasks[1].status, TaskStatus::Failed);
+}
+
+#[test]
+fn failure_reporting() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[("apertium-eng.eng.lexc", "LEXICON Nouns\ncat # ;\n")],
+        "Add lexc",
+    );
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+
+    let response = client.post("/apertium-eng/lexc?async=false").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert!(body["stats"].as_array().expect("valid stats").is_empty());
+    let errors = body["errors"].as_array().expect("valid errors");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["kind"], "Lexc");
+    assert_eq!(errors[0]["error_kind"], "Lexc");
+    assert_eq!(errors[0]["message"], "Missing Root lexicon");
+    assert_eq!(errors[0]["file"]["path"], "apertium-eng.eng.lexc");
+    let sha = errors[0]["file"]["sha"].clone();
+
+    for endpoint in &["/apertium-eng/lexc", "/apertium-eng/errors"] {
+        let response = client.get(endpoint.to_string()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = parse_response(response);
+        let errors = body["errors"].as_array().expect("valid errors");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["message"], "Missing Root lexicon");
+        assert_eq!(errors[0]["file"]["sha"], sha);
+    }
+
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[("apertium-eng.eng.lexc", "LEXICON Root\ncat # ;\n")],
+        "Fix lexc",
+    );
+    let response = client.post("/apertium-eng/lexc?async=false").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let response = client.get("/apertium-eng/errors").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert!(body["errors"].as_array().expect("valid errors").is_empty());
+
+    let response = client.get("/apertium-eng/monodix?async=false").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert!(body["errors"].as_array().expect("valid errors").is_empty());
+}
+
+#[test]
+fn package_history() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+
+    let response = client.post("/apertium-eng/monodix?async=false").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[(
+            "apertium-eng.eng.dix",
+            "<dictionary><section><e lm=\"cat\"></e></section></dictionary>",
+        )],
+        "Add stem",
+    );
+    for _ in 0..2 {
+        let response = client.post("/apertium-eng/monodix?async=false").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    let response = client.get("/apertium-eng/monodix/history?stat_kind=stems").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    let history = body["history"].as_array().expect("valid history");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["path"], "apertium-eng.eng.dix");
+    assert_eq!(history[0]["stat_kind"], "Stems");
+    let values = history[0]["values"].as_array().expect("valid values");
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0]["value"], 0);
+    assert_eq!(values[0]["revision"], 2);
+    assert_eq!(values[1]["value"], 1);
+    assert_eq!(values[1]["revision"], 3);
+
+    let response = client.get("/apertium-eng/history").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert_eq!(body["history"].as_array().expect("valid history").len(), 16);
+
+    let response = client.get("/apertium-eng/history?since=2100-01-01").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert!(body["history"].as_array().expect("valid history").is_empty());
+
+    let response = client.get("/apertium-eng/history?until=yesterday").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn package_backfill() {
+    use chrono::NaiveDate;
+    use rocket::http::Header;
+
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[(
+            "apertium-eng.eng.dix",
+            "<dictionary><section><e lm=\"cat\"></e></section></dictionary>",
+        )],
+        "Add stem",
+    );
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let rocket = service(db_path.into(), Arc::new(source)).manage(AdminAuthToken("secret".to_string()));
+    let client = Client::new(rocket).expect("valid rocket instance");
+    let authorization = || Header::new("Authorization", "Bearer secret");
+
+    let response = client.post("/apertium-eng/backfill?every=1").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    for endpoint in &[
+        "/apertium-eng/backfill?monthly=false",
+        "/apertium-eng/backfill?every=0",
+        "/apertium-eng/backfill?every=1&monthly=true",
+    ] {
+        let response = client.post(*endpoint).header(authorization()).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    let response = client
+        .post("/apertium-eng/backfill?every=1")
+        .header(authorization())
+        .dispatch();
+    assert_eq!(response.status(), Status::Accepted);
+    let body = parse_response(response);
+    assert_eq!(body["snapshots"].as_array().expect("valid snapshots").len(), 3);
+    let branch = body["branch"].as_str().expect("valid branch").to_string();
+
+    // One value per version of the monodix, the rlx is outside the non-recursive listing
+    wait_for_ok(&client, "/apertium-eng/monodix/history?stat_kind=stems", |response| {
+        let body = parse_response(response);
+        body["history"][0]["values"]
+            .as_array()
+            .map_or(false, |values| values.len() == 3)
+    });
+
+    // Entries from before the Git backend carry revision numbers above those counted by Git
+    let conn = SqliteConnection::establish(db_path).expect("valid database connection");
+    let last_changed = NaiveDate::from_ymd(2018, 1, 1).and_hms(0, 0, 0);
+    diesel::insert_into(entries_db::table)
+        .values(&NewEntry {
+            requested: last_changed,
+            created: last_changed,
+            name: "apertium-eng".to_string(),
+            revision: 80000,
+            sha: "0".repeat(40),
+            path: "apertium-eng.eng.dix".to_string(),
+            last_changed,
+            last_author: "Test Author".to_string(),
+            size: 10,
+            file_kind: FileKind::Monodix,
+            stat_kind: StatKind::Stems,
+            value: json!(42).into(),
+            branch,
+        })
+        .execute(&conn)
+        .expect("valid entry insertion");
+
+    let response = client.get("/apertium-eng/monodix").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    let stems = find_stat(body["stats"].as_array().expect("valid stats"), "Stems");
+    assert_eq!(stems["value"], 1);
+    assert_eq!(stems["revision"], 3);
+
+    let response = client
+        .post("/apertium-eng/backfill?every=1")
+        .header(authorization())
+        .dispatch();
+    assert_eq!(response.status(), Status::Accepted);
+}
+
+#[test]
+fn package_compare() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[(
+            "apertium-eng.eng.dix",
+            "<dictionary><section><e lm=\"cat\"></e><e lm=\"dog\"></e></section></dictionary>",
+        )],
+        "Add stems",
+    );
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+
+    for endpoint in &[
+        "/apertium-eng/compare?to=HEAD",
+        "/apertium-eng/compare?from=--output=/tmp/compare&to=HEAD",
+        "/apertium-eng/compare?from=HEAD~2&to=missing",
+    ] {
+        let response = client.get(*endpoint).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    // Without a to revision, the head of the default branch is compared
+    for endpoint in &[
+        "/apertium-eng/compare?from=HEAD~2&to=HEAD",
+        "/apertium-eng/compare?from=HEAD~2",
+    ] {
+        let response = client.get(*endpoint).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = parse_response(response);
+        let stats = body["stats"].as_array().expect("valid stats");
+        assert_eq!(stats.len(), 16);
+        assert_eq!(find_stat(stats, "Paradigms")["delta"], 0);
+        let stems = find_stat(stats, "Stems");
+        assert_eq!(stems["from"], 0);
+        assert_eq!(stems["to"], 2);
+        assert_eq!(stems["delta"], 2);
+
+        let totals = body["totals"].as_array().expect("valid totals");
+        assert_eq!(totals.len(), 6);
+        assert_eq!(find_stat(totals, "Stems")["delta"], 2);
+    }
+
+    // Compared statistics aren't recorded, so those of the branch itself are still to be computed
+    let response = client.get("/apertium-eng/monodix").dispatch();
+    assert_eq!(response.status(), Status::Accepted);
+}
+
+#[test]
+fn aggregate_stats() {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    setup_package(checkouts_dir.path(), "apertium-eng");
+    commit_files(
+        &checkouts_dir.path().join("apertium-eng"),
+        &[(
+            "apertium-eng.eng.dix",
+            "<dictionary><section><e lm=\"cat\"></e><e lm=\"dog\"></e></section></dictionary>",
+        )],
+        "Add stems",
+    );
+    let pair_dir = checkouts_dir.path().join("apertium-eng-spa");
+    fs::create_dir_all(&pair_dir).expect("valid repository directory");
+    run_git(&pair_dir, &["init", "--quiet"]);
+    commit_files(
+        &pair_dir,
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            "<dictionary><section><e></e><e></e><e></e></section></dictionary>",
+        )],
+        "Initial commit",
+    );
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+
+    for endpoint in &["/apertium-eng?async=false", "/apertium-eng-spa?async=false"] {
+        let response = client.post(*endpoint).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    let response = client.get("/stats").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    let mut packages = body["packages"].as_array().expect("valid packages").clone();
+    packages.sort_by_key(|package| package["name"].as_str().expect("valid name").to_string());
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0]["name"], "apertium-eng");
+    assert_eq!(packages[0]["kind"], "Module");
+    assert_eq!(packages[1]["name"], "apertium-eng-spa");
+    assert_eq!(packages[1]["kind"], "Pair");
+    let pair_totals = packages[1]["totals"].as_array().expect("valid totals");
+    assert_eq!(find_stat(pair_totals, "Entries")["value"], 3);
+
+    let modules = body["totals"]["modules"].as_array().expect("valid module totals");
+    assert_eq!(modules.len(), 6);
+    assert_eq!(find_stat(modules, "Stems")["value"], 2);
+    let pairs = body["totals"]["pairs"].as_array().expect("valid pair totals");
+    assert_eq!(find_stat(pairs, "Entries")["value"], 3);
+
+    let response = client.get("/stats?stat_kind=stems").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    let packages = body["packages"].as_array().expect("valid packages");
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0]["name"], "apertium-eng");
+
+    let response = client.get("/stats?topic=apertium-trunk").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = parse_response(response);
+    assert!(body["packages"].as_array().expect("valid packages").is_empty());
+
+    let response = client.get("/stats?kind=unknown").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
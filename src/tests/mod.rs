@@ -1,9 +1,13 @@
 #[macro_use]
 mod common;
 mod get;
+mod git;
 mod post;
+mod stats;
 
 use std::{
+    fs,
+    path::Path,
     process::{Command, Stdio},
     thread::sleep,
     time::Duration,
@@ -13,7 +17,7 @@ use rocket::{
     http::{Accept, Status},
     local::{Client, LocalResponse},
 };
-use tempfile::NamedTempFile;
+use tempfile::{NamedTempFile, TempDir};
 
 use super::*;
 
@@ -22,19 +26,31 @@ pub const MAX_WAIT_DURATION: Duration = Duration::from_secs(64);
 
 pub const TEST_LT_MODULE: &str = "eng";
 pub const TEST_LT_MODULE_FILES_COUNT: usize = 3;
-pub const TEST_LT_MODULE_STATS_COUNT: usize = 4;
+pub const TEST_LT_MODULE_STATS_COUNT: usize = 31;
 
 pub const TEST_HFST_MODULE: &str = "kaz";
 pub const TEST_HFST_MODULE_FILES_COUNT: usize = 5;
-pub const TEST_HFST_MODULE_STATS_COUNT: usize = 6;
+pub const TEST_HFST_MODULE_STATS_COUNT: usize = 14;
 
 pub const TEST_HFST_PAIR: &str = "kaz-tat";
-pub const TEST_HFST_PAIR_FILES_COUNT: usize = 7;
-pub const TEST_HFST_PAIR_STATS_COUNT: usize = 11;
+pub const TEST_HFST_PAIR_FILES_COUNT: usize = 9;
+pub const TEST_HFST_PAIR_STATS_COUNT: usize = 54;
 
 pub const TEST_LT_PAIR: &str = "oci-cat";
-pub const TEST_LT_PAIR_FILES_COUNT: usize = 7;
-pub const TEST_LT_PAIR_STATS_COUNT: usize = 13;
+pub const TEST_LT_PAIR_FILES_COUNT: usize = 9;
+pub const TEST_LT_PAIR_STATS_COUNT: usize = 68;
+
+pub const POSSIBLY_ZERO_STAT_KINDS: &[&str] = &[
+    "Macros",
+    "RegexEntries",
+    "MultiwordEntries",
+    "IgnoredEntries",
+    "Attributes",
+    "Variables",
+    "Lists",
+    "AmbiguousLeftLemmas",
+    "AmbiguousRightLemmas",
+];
 
 #[test]
 fn usage_plaintext() {
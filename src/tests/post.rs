@@ -17,7 +17,7 @@ fn package_stats() {
                 stats.sort_by_key(|entry| entry["stat_kind"].as_str().expect("stat_kind is string").to_string());
                 let created = stats[0]["created"].as_str().expect("created is string");
 
-                let response = client.post(endpoint.clone()).dispatch();
+                let response = client.post(format!("{}?force=true", endpoint)).dispatch();
                 assert_eq!(response.status(), Status::Accepted);
                 let mut body = parse_response(response);
                 let in_progress = body["in_progress"].as_array_mut().expect("valid in_progress");
@@ -70,7 +70,7 @@ fn package_specific_stats() {
                 });
                 let created = stats[0]["created"].as_str().expect("created is string");
 
-                let response = client.post(endpoint.clone()).dispatch();
+                let response = client.post(format!("{}?force=true", endpoint)).dispatch();
                 assert_eq!(response.status(), Status::Accepted);
                 let mut body = parse_response(response);
                 let in_progress = body["in_progress"].as_array_mut().expect("valid in_progress");
@@ -105,6 +105,37 @@ fn package_specific_stats() {
     });
 }
 
+#[test]
+fn unchanged_package_stats() {
+    let module = format!("apertium-{}", TEST_LT_MODULE);
+    let endpoint = format!("/{}/monodix?async=false", module);
+
+    run_test!(|client| {
+        let response = client.get(endpoint.clone()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let mut body = parse_response(response);
+        let stats = body["stats"].as_array_mut().expect("valid stats");
+        stats.sort_by_key(|entry| entry["stat_kind"].as_str().expect("stat_kind is string").to_string());
+
+        let response = client.post(endpoint.clone()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let mut body = parse_response(response);
+        assert!(body["in_progress"].as_array().expect("valid in_progress").is_empty());
+        let reused_stats = body["stats"].as_array_mut().expect("valid stats");
+        reused_stats.sort_by_key(|entry| entry["stat_kind"].as_str().expect("stat_kind is string").to_string());
+
+        assert_eq!(reused_stats.len(), stats.len());
+        for (stat, reused_stat) in stats.iter().zip(reused_stats.iter()) {
+            assert_eq!(stat["sha"], reused_stat["sha"]);
+            assert_eq!(stat["value"], reused_stat["value"]);
+            assert!(
+                reused_stat["created"].as_str().expect("created is string")
+                    > stat["created"].as_str().expect("created is string")
+            );
+        }
+    });
+}
+
 #[test]
 fn update_package_listing() {
     run_test_with_github_auth!(|client| {
@@ -0,0 +1,622 @@
+use self::common::*;
+use super::*;
+
+fn get_file_response(package: &str, kind: &str, files: &[(&str, &str)]) -> JsonValue {
+    let checkouts_dir = TempDir::new().expect("valid checkouts directory");
+    let repo_dir = checkouts_dir.path().join(package);
+    fs::create_dir_all(&repo_dir).expect("valid repository directory");
+    run_git(&repo_dir, &["init", "--quiet"]);
+    commit_files(&repo_dir, files, "Initial commit");
+    let source = LocalProvider::new(checkouts_dir.path().to_path_buf());
+
+    let db_file = setup_database();
+    let db_path = db_file.path().to_str().expect("valid database path");
+    let client = Client::new(service(db_path.into(), Arc::new(source))).expect("valid rocket instance");
+
+    let response = client.get(format!("/{}/{}?async=false", package, kind)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    parse_response(response)
+}
+
+fn get_file_stats(package: &str, kind: &str, files: &[(&str, &str)]) -> Vec<serde_json::Value> {
+    let body = get_file_response(package, kind, files);
+    assert!(
+        body["errors"].as_array().expect("valid errors").is_empty(),
+        "{}",
+        body["errors"]
+    );
+    body["stats"].as_array().expect("valid stats").clone()
+}
+
+fn get_file_errors(package: &str, kind: &str, files: &[(&str, &str)]) -> Vec<serde_json::Value> {
+    let body = get_file_response(package, kind, files);
+    assert!(
+        body["stats"].as_array().expect("valid stats").is_empty(),
+        "{}",
+        body["stats"]
+    );
+    body["errors"].as_array().expect("valid errors").clone()
+}
+
+fn get_stat(stats: &[serde_json::Value], stat_kind: &str) -> serde_json::Value {
+    find_stat(stats, stat_kind)["value"].clone()
+}
+
+#[test]
+fn monodix_stems_by_pos() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "monodix",
+        &[(
+            "apertium-eng.eng.dix",
+            r#"<dictionary>
+                <pardefs>
+                    <pardef n="house__n"><e><p><l/><r><s n="n"/><s n="sg"/></r></p></e></pardef>
+                    <pardef n="run__vblex"><e><p><l/><r><s n="vblex"/><s n="inf"/></r></p></e></pardef>
+                    <pardef n="quick"><e><p><l/><r><s n="adj"/></r></p></e></pardef>
+                </pardefs>
+                <section id="main" type="standard">
+                    <e lm="house"><i>house</i><par n="house__n"/></e>
+                    <e lm="cat"><i>cat</i><par n="house__n"/></e>
+                    <e lm="run"><i>run</i><par n="run__vblex"/></e>
+                    <e lm="quick"><i>quick</i><par n="quick"/></e>
+                    <e lm="the"><p><l>the</l><r>the<s n="det"/></r></p></e>
+                    <e lm="ok"><i>ok</i></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(get_stat(&stats, "Stems"), 6);
+    assert_eq!(get_stat(&stats, "Paradigms"), 3);
+    assert_eq!(
+        get_stat(&stats, "StemsByPos"),
+        serde_json::json!({"adj": 1, "det": 1, "n": 2, "unknown": 1, "vblex": 1})
+    );
+}
+
+#[test]
+fn monodix_paradigm_usage() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "monodix",
+        &[(
+            "apertium-eng.eng.dix",
+            r#"<dictionary>
+                <pardefs>
+                    <pardef n="-s__n"><e><p><l>s</l><r><s n="pl"/></r></p></e></pardef>
+                    <pardef n="house__n"><e><p><l/><r><s n="n"/></r></p></e><e><par n="-s__n"/></e></pardef>
+                    <pardef n="ox__n"><e><p><l/><r><s n="n"/></r></p></e></pardef>
+                    <pardef n="dead__adj"><e><p><l/><r><s n="adj"/></r></p></e></pardef>
+                </pardefs>
+                <section id="main" type="standard">
+                    <e lm="house"><i>house</i><par n="house__n"/></e>
+                    <e lm="cat"><i>cat</i><par n="house__n"/></e>
+                    <e lm="ox"><i>ox</i><par n="ox__n"/></e>
+                    <e lm="run"><i>run</i><par n="run__vlbex"/></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(
+        get_stat(&stats, "ParadigmUsage"),
+        serde_json::json!({"-s__n": 0, "dead__adj": 0, "house__n": 2, "ox__n": 1})
+    );
+    assert_eq!(get_stat(&stats, "UnusedParadigms"), serde_json::json!(["dead__adj"]));
+    assert_eq!(
+        get_stat(&stats, "UndefinedParadigms"),
+        serde_json::json!(["run__vlbex"])
+    );
+}
+
+#[test]
+fn bidix_symbols() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "bidix",
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            r#"<dictionary>
+                <sdefs>
+                    <sdef n="n"/>
+                    <sdef n="vblex"/>
+                    <sdef n="adj"/>
+                </sdefs>
+                <section id="main" type="standard">
+                    <e><p><l>cat<s n="n"/></l><r>gato<s n="n"/></r></p></e>
+                    <e><p><l>run<s n="vblex"/></l><r>correr<s n="vbmod"/></r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(get_stat(&stats, "Entries"), 2);
+    assert_eq!(get_stat(&stats, "Symbols"), 3);
+    assert_eq!(get_stat(&stats, "UnusedSymbols"), serde_json::json!(["adj"]));
+    assert_eq!(get_stat(&stats, "UndefinedSymbols"), serde_json::json!(["vbmod"]));
+}
+
+#[test]
+fn bidix_entries_by_direction() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "bidix",
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>cat</l><r>gato</r></p></e>
+                    <e r="LR"><p><l>kitten</l><r>gato</r></p></e>
+                    <e r="RL" srl="sense2"><p><l>feline</l><r>gato</r></p></e>
+                    <e slr="sense1"><p><l>run</l><r>correr</r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(get_stat(&stats, "Entries"), 4);
+    assert_eq!(
+        get_stat(&stats, "EntriesByDirection"),
+        serde_json::json!({"bidirectional": 2, "lr": 1, "rl": 1, "slr": 1, "srl": 1})
+    );
+}
+
+#[test]
+fn bidix_ambiguity() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "bidix",
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>cat<s n="n"/></l><r>gato<s n="n"/></r></p></e>
+                    <e r="LR"><p><l>cat<s n="n"/></l><r>felino<s n="n"/></r></p></e>
+                    <e><p><l>kitty<b/>cat<s n="n"/></l><r>gato<s n="n"/></r></p></e>
+                    <e r="RL"><p><l>moggy<s n="n"/></l><r>gato<s n="n"/></r></p></e>
+                    <e><i>mosquito<s n="n"/></i></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(get_stat(&stats, "AmbiguousLeftLemmas"), 1);
+    assert_eq!(get_stat(&stats, "AmbiguousRightLemmas"), 1);
+    assert_eq!(
+        get_stat(&stats, "LeftFanOut"),
+        serde_json::json!({"max": 2, "average": 1.33})
+    );
+    assert_eq!(
+        get_stat(&stats, "RightFanOut"),
+        serde_json::json!({"max": 3, "average": 2.0})
+    );
+}
+
+#[test]
+fn transfer_stages() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "t1x",
+        &[
+            (
+                "apertium-eng-spa.eng-spa.t1x",
+                r#"<transfer default="chunk">
+                    <section-def-cats>
+                        <def-cat n="nom"><cat-item tags="n.*"/></def-cat>
+                        <def-cat n="adj"><cat-item tags="adj.*"/></def-cat>
+                    </section-def-cats>
+                    <section-def-attrs>
+                        <def-attr n="nbr"><attr-item tags="sg"/><attr-item tags="pl"/></def-attr>
+                    </section-def-attrs>
+                    <section-def-vars>
+                        <def-var n="number"/>
+                    </section-def-vars>
+                    <section-def-lists>
+                        <def-list n="days"><list-item v="monday"/></def-list>
+                    </section-def-lists>
+                    <section-def-macros>
+                        <def-macro n="f_nbr" npar="1"><let><var n="number"/><lit v=""/></let></def-macro>
+                        <def-macro n="f_unused" npar="1"><let><var n="number"/><lit v=""/></let></def-macro>
+                    </section-def-macros>
+                    <section-rules>
+                        <rule><pattern><pattern-item n="nom"/></pattern><action><call-macro n="f_nbr"><with-param pos="1"/></call-macro></action></rule>
+                        <rule><pattern><pattern-item n="adj"/><pattern-item n="nom"/></pattern><action/></rule>
+                        <rule><pattern><pattern-item n="nom"/><pattern-item n="adj"/></pattern><action/></rule>
+                    </section-rules>
+                </transfer>"#,
+            ),
+            (
+                "apertium-eng-spa.eng-spa.t2x",
+                r#"<interchunk><section-rules/></interchunk>"#,
+            ),
+        ],
+    );
+
+    assert!(stats.iter().all(|entry| entry["file_kind"] == "T1x"));
+    assert_eq!(get_stat(&stats, "Rules"), 3);
+    assert_eq!(get_stat(&stats, "Macros"), 2);
+    assert_eq!(get_stat(&stats, "Categories"), 2);
+    assert_eq!(get_stat(&stats, "Attributes"), 1);
+    assert_eq!(get_stat(&stats, "Variables"), 1);
+    assert_eq!(get_stat(&stats, "Lists"), 1);
+    assert_eq!(
+        get_stat(&stats, "RulePatternLengths"),
+        serde_json::json!({"1": 1, "2": 2})
+    );
+    assert_eq!(get_stat(&stats, "UnusedMacros"), serde_json::json!(["f_unused"]));
+}
+
+#[test]
+fn monodix_section_types() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "monodix",
+        &[(
+            "apertium-eng.eng.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e lm="cat"><i>cat</i><par n="house__n"/></e>
+                    <e lm="number"><re>[0-9]+</re><p><l/><r><s n="num"/></r></p></e>
+                </section>
+                <section id="punctuation" type="inconditional">
+                    <e lm="."><i>.</i><par n="sent"/></e>
+                    <e lm=","><i>,</i><par n="cm"/></e>
+                </section>
+                <section id="final" type="postblank">
+                    <e><i>'s</i></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+
+    assert_eq!(get_stat(&stats, "Stems"), 1);
+    assert_eq!(get_stat(&stats, "RegexEntries"), 1);
+    assert_eq!(
+        get_stat(&stats, "EntriesBySectionType"),
+        serde_json::json!({"inconditional": 2, "postblank": 1, "standard": 2})
+    );
+}
+
+#[test]
+fn multiword_entries() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "monodix",
+        &[(
+            "apertium-eng.eng.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e lm="cat"><i>cat</i><par n="house__n"/></e>
+                    <e lm="ice cream"><i>ice<b/>cream</i><par n="house__n"/></e>
+                    <e lm="take out"><i>take</i><par n="take__vblex"/><p><l><g><b/>out</g></l><r><g><b/>out</g></r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Stems"), 3);
+    assert_eq!(get_stat(&stats, "MultiwordEntries"), 2);
+
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "bidix",
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>cat</l><r>gato</r></p></e>
+                    <e><p><l>ice<b/>cream</l><r>helado</r></p></e>
+                    <e><p><l>take<g><b/>out</g></l><r>sacar</r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Entries"), 3);
+    assert_eq!(get_stat(&stats, "MultiwordEntries"), 2);
+}
+
+#[test]
+fn variant_and_ignored_entries() {
+    let stats = get_file_stats(
+        "apertium-cat",
+        "monodix",
+        &[(
+            "apertium-cat.cat.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e lm="aquest"><i>aquest</i><par n="aquest__prn"/></e>
+                    <e lm="aquest" v="val"><i>este</i><par n="aquest__prn"/></e>
+                    <e lm="eixir" v="val"><i>eix</i><par n="eix/ir__vblex"/></e>
+                    <e lm="tindre" v="bal"><i>tind</i><par n="tind/re__vblex"/></e>
+                    <e lm="typo" i="yes"><i>tipo</i><par n="house__n"/></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Stems"), 4);
+    assert_eq!(get_stat(&stats, "IgnoredEntries"), 1);
+    assert_eq!(
+        get_stat(&stats, "EntriesByVariant"),
+        serde_json::json!({"v": {"bal": 1, "val": 2}})
+    );
+
+    let stats = get_file_stats(
+        "apertium-nno-nob",
+        "bidix",
+        &[(
+            "apertium-nno-nob.nno-nob.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>eg</l><r>jeg</r></p></e>
+                    <e vl="e_infinitive"><p><l>kaste</l><r>kaste</r></p></e>
+                    <e vr="a_infinitive"><p><l>kasta</l><r>kaste</r></p></e>
+                    <e i="yes"><p><l>ikkje</l><r>ikke</r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Entries"), 3);
+    assert_eq!(get_stat(&stats, "IgnoredEntries"), 1);
+    assert_eq!(
+        get_stat(&stats, "EntriesByVariant"),
+        serde_json::json!({"vl": {"e_infinitive": 1}, "vr": {"a_infinitive": 1}})
+    );
+}
+
+#[test]
+fn duplicate_entries() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "monodix",
+        &[(
+            "apertium-eng.eng.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e lm="house"><i>house</i><par n="house__n"/></e>
+                    <e lm="house" c="added twice"><i>house</i><par n="house__n"/></e>
+                    <e lm="house"><i>hous</i><par n="liv/e__vblex"/></e>
+                    <e lm="house" r="LR"><i>house</i><par n="house__n"/></e>
+                    <e lm="house"> <i>house</i> <par n="house__n"/></e>
+                    <e lm="house"><i>houses</i><par n="house__n"/></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(
+        get_stat(&stats, "DuplicateEntries"),
+        serde_json::json!([{"entry": {"lemma": "house", "paradigms": ["house__n"]}, "lines": [3, 4, 7]}])
+    );
+    assert_eq!(
+        get_stat(&stats, "NearDuplicateEntries"),
+        serde_json::json!([{"entry": {"lemma": "house", "paradigms": ["house__n"]}, "lines": [3, 4, 6, 7, 8]}])
+    );
+
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "bidix",
+        &[(
+            "apertium-eng-spa.eng-spa.dix",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>ice<b/>cream<s n="n"/></l><r>helado<s n="n"/></r></p></e>
+                    <e><p><l>cat<s n="n"/></l><r>gato<s n="n"/></r></p></e>
+                    <e><p><l>cat<s n="n"/></l><r>gato<s n="adj"/></r></p></e>
+                    <e a="someone"><p><l>ice<b/>cream<s n="n"/></l><r>helado<s n="n"/></r></p></e>
+                    <e><p><l>cat<s n="n"/></l><r>gato<s n="n"/></r></p><par n="m_f"/></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(
+        get_stat(&stats, "DuplicateEntries"),
+        serde_json::json!([{"entry": {"left": "ice cream", "right": "helado"}, "lines": [3, 6]}])
+    );
+    assert_eq!(
+        get_stat(&stats, "NearDuplicateEntries"),
+        serde_json::json!([{"entry": {"left": "cat", "right": "gato"}, "lines": [4, 5, 7]}])
+    );
+}
+
+#[test]
+fn lexical_selection_rules() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "lrx",
+        &[(
+            "apertium-eng-spa.eng-spa.lrx",
+            r#"<rules>
+                <rule weight="0.8">
+                    <match lemma="bank" tags="n.*"><select lemma="banco" tags="n.*"/></match>
+                    <match lemma="account" tags="n.*"/>
+                </rule>
+                <rule weight="0.5">
+                    <match lemma="river" tags="n.*"/>
+                    <match lemma="bank" tags="n.*"><select lemma="orilla" tags="n.*"/></match>
+                </rule>
+                <rule>
+                    <match tags="det.*"/>
+                    <match lemma="letter" tags="n.*"><remove lemma="letra" tags="n.*"/></match>
+                </rule>
+            </rules>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Rules"), 3);
+    assert_eq!(get_stat(&stats, "CoveredLemmas"), 2);
+    assert_eq!(
+        get_stat(&stats, "RuleWeights"),
+        serde_json::json!({"0.5": 1, "0.8": 1, "1": 1})
+    );
+}
+
+#[test]
+fn recursive_transfer_rules() {
+    let stats = get_file_stats(
+        "apertium-eng-kir",
+        "rtx",
+        &[(
+            "apertium-eng-kir.eng-kir.rtx",
+            r#"! Attribute categories
+gender = m f GD ;
+number = (ND sg) sg pl ND ;
+
+number > num : sg sg, pl pl ;
+
+! Output patterns
+n: _.number ;
+NP: _.number ;
+det_mac: (if (1.number = sg) [1] else [1(det)]) ;
+
+NP -> det n { 1 _ 2 } | n { 1 } ;
+NP -> 2: adj NP { 2 _ 1 } ; ! "quoted; text"
+"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Rules"), 3);
+    assert_eq!(get_stat(&stats, "OutputPatterns"), 2);
+    assert_eq!(get_stat(&stats, "Attributes"), 2);
+    assert_eq!(get_stat(&stats, "Macros"), 1);
+}
+
+#[test]
+fn separable_entries() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "lsx",
+        &[(
+            "apertium-eng.eng.lsx",
+            r#"<dictionary>
+                <pardefs>
+                    <pardef n="SN">
+                        <e><i><w/><s n="n"/><t/><j/></i></e>
+                        <e><i><w/><s n="det"/><t/><j/><w/><s n="n"/><t/><j/></i></e>
+                    </pardef>
+                </pardefs>
+                <section id="main" type="standard">
+                    <e lm="take out"><i>take<s n="vblex"/><t/><j/></i><par n="SN"/><i>out<s n="adv"/><j/></i></e>
+                    <e lm="give up"><i>give<s n="vblex"/><t/><j/>up<s n="adv"/><j/></i></e>
+                    <e lm="typo" i="yes"><i>tyop<j/></i></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "SeparableEntries"), 2);
+    assert_eq!(get_stat(&stats, "LongestEntry"), 4);
+}
+
+#[test]
+fn separable_pair_entries() {
+    let stats = get_file_stats(
+        "apertium-eng",
+        "lsx",
+        &[(
+            "apertium-eng.eng.lsx",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e lm="a lot of"><p><l>a<j/>lot<j/>of<j/></l><r>a_lot_of<j/></r></p></e>
+                    <e lm="give up"><p><l>give<s n="vblex"/><j/>up<j/></l><r>give# up<s n="vblex"/><j/></r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "SeparableEntries"), 2);
+    assert_eq!(get_stat(&stats, "LongestEntry"), 3);
+}
+
+#[test]
+fn anaphora_rules() {
+    let stats = get_file_stats(
+        "apertium-eng-spa",
+        "arx",
+        &[(
+            "apertium-eng-spa.spa-eng.arx",
+            r#"<ref>
+                <section-def-cats>
+                    <def-cat n="det"><cat-item tags="det.*"/></def-cat>
+                </section-def-cats>
+                <section-markables>
+                    <markable n="NP">
+                        <pattern><pattern-item n="det"/><pattern-item n="n"/></pattern>
+                    </markable>
+                    <markable n="PP"/>
+                </section-markables>
+                <section-parameters>
+                    <anaphor><parameter-item has-tags="prn.pos"/></anaphor>
+                    <antecedent><parameter-item has-tags="n" score="2"/></antecedent>
+                    <delimiter/>
+                </section-parameters>
+            </ref>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Markables"), 2);
+    assert_eq!(get_stat(&stats, "Parameters"), 3);
+
+    let stats = get_file_stats(
+        "apertium-eng",
+        "postdix",
+        &[(
+            "apertium-eng.eng.postgen",
+            r#"<dictionary>
+                <section id="main" type="standard">
+                    <e><p><l>a<b/></l><r>an<b/></r></p></e>
+                </section>
+            </dictionary>"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Entries"), 1);
+}
+
+#[test]
+fn twol_rules() {
+    let stats = get_file_stats(
+        "apertium-kaz",
+        "twol",
+        &[(
+            "apertium-kaz.kaz.twol",
+            r#"Alphabet
+  а ә б %{A%}:а %{A%}:е %{A%}:0 %> ;
+
+Diacritics
+  %{d%} ;
+
+Sets
+  Vow = а ә е ; ! "not a rule"
+  BackVow = а ;
+
+Definitions
+  Boundary = [ %> | #: ] ;
+
+Rules
+
+"Vowel harmony"
+%{A%}:а <=> BackVow: [ \Vow: ]* _ ;
+
+   "Indented rule"
+%{A%}:0 <=> Vow: %>: _ ;
+            _ %>: ;
+"#,
+        )],
+    );
+    assert_eq!(get_stat(&stats, "Rules"), 2);
+    assert_eq!(get_stat(&stats, "AlphabetSize"), 7);
+    assert_eq!(get_stat(&stats, "Sets"), 2);
+    assert_eq!(get_stat(&stats, "Definitions"), 1);
+    assert_eq!(get_stat(&stats, "Diacritics"), 1);
+}
+
+#[test]
+fn malformed_twol() {
+    let bodies = [
+        ("Alphabet\n  a b %", "Unable to parse L2: dangling %"),
+        ("Rules\n\"Unterminated\n", "Unable to parse L2: unterminated string"),
+        ("a:b <=> _ ;\n", "Unable to parse L1: expected section header"),
+        ("Sets\n  Vow a e ;\n", "Unable to parse L2: expected NAME = ..."),
+    ];
+
+    for (body, message) in &bodies {
+        let errors = get_file_errors("apertium-kaz", "twol", &[("apertium-kaz.kaz.twol", body)]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["kind"], "Twol");
+        assert_eq!(errors[0]["error_kind"], "Twol");
+        assert_eq!(errors[0]["message"], *message);
+    }
+}
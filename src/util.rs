@@ -7,6 +7,7 @@ use std::{
     ops::Try,
 };
 
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::{
     backend::Backend,
     deserialize::{self, FromSql},
@@ -19,8 +20,9 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use rocket::{
     http::Status,
+    request::{self, FromRequest},
     response::{Responder, Response},
-    FromForm, Request,
+    FromForm, Outcome, Request, State,
 };
 use rocket_contrib::json::JsonValue as RocketJsonValue;
 use serde_derive::Serialize;
@@ -47,6 +49,8 @@ lazy_static! {
             })
             .collect()
     };
+    pub static ref MODULE_RE: Regex = Regex::new(&format!(r"^apertium-{re}$", re = LANG_CODE_RE)).unwrap();
+    pub static ref PAIR_RE: Regex = Regex::new(&format!(r"^apertium-{re}-{re}$", re = LANG_CODE_RE)).unwrap();
 }
 
 fn convert_language_code(code: &str, sub_code: Option<&str>) -> Option<String> {
@@ -69,11 +73,6 @@ pub fn normalize_name<H: BuildHasher>(name: &str, package_names: HashSet<String,
         format!("apertium-{}", name)
     };
 
-    lazy_static! {
-        static ref MODULE_RE: Regex = Regex::new(&format!(r"^apertium-{re}$", re = LANG_CODE_RE)).unwrap();
-        static ref PAIR_RE: Regex = Regex::new(&format!(r"^apertium-{re}-{re}$", re = LANG_CODE_RE)).unwrap();
-    }
-
     if package_names.contains(&normalized_name) {
         return Ok(normalized_name);
     }
@@ -119,6 +118,12 @@ pub fn normalize_name<H: BuildHasher>(name: &str, package_names: HashSet<String,
     }
 }
 
+pub fn parse_datetime(value: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_hms(0, 0, 0)))
+        .map_err(|_| format!("Invalid date: {}", value))
+}
+
 pub enum JsonResult {
     Ok(RocketJsonValue),
     Err(Option<RocketJsonValue>, Status),
@@ -162,6 +167,27 @@ impl<'r> Responder<'r> for JsonResult {
     }
 }
 
+pub struct AdminAuthToken(pub String);
+
+pub struct Admin;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Admin, ()> {
+        let expected_authorization = request
+            .guard::<State<AdminAuthToken>>()
+            .succeeded()
+            .map(|token| format!("Bearer {}", token.0));
+
+        match (expected_authorization, request.headers().get_one("Authorization")) {
+            (Some(expected), Some(actual)) if expected == actual => Outcome::Success(Admin),
+            (Some(_), _) => Outcome::Failure((Status::Unauthorized, ())),
+            (None, _) => Outcome::Failure((Status::Forbidden, ())),
+        }
+    }
+}
+
 #[derive(SqlType)]
 #[sqlite_type = "Text"]
 pub struct JsonType;
@@ -196,6 +222,8 @@ impl From<RocketJsonValue> for JsonValue {
 #[derive(FromForm)]
 pub struct Params {
     pub recursive: Option<bool>,
+    pub branch: Option<String>,
+    pub force: Option<bool>,
 
     #[form(field = "async")]
     pub r#async: Option<bool>,
@@ -209,13 +237,58 @@ impl Params {
     pub fn is_recursive(&self) -> bool {
         self.recursive.unwrap_or(false)
     }
+
+    pub fn is_force(&self) -> bool {
+        self.force.unwrap_or(false)
+    }
 }
 
 impl Default for Params {
     fn default() -> Self {
         Self {
             recursive: None,
+            branch: None,
+            force: None,
             r#async: Some(true),
         }
     }
 }
+
+#[derive(FromForm, Default)]
+pub struct HistoryParams {
+    pub branch: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub stat_kind: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(FromForm, Default)]
+pub struct BackfillParams {
+    pub branch: Option<String>,
+    pub recursive: Option<bool>,
+    pub every: Option<usize>,
+    pub monthly: Option<bool>,
+}
+
+#[derive(FromForm, Default)]
+pub struct CompareParams {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub branch: Option<String>,
+    pub kind: Option<String>,
+    pub recursive: Option<bool>,
+}
+
+impl CompareParams {
+    pub fn is_recursive(&self) -> bool {
+        self.recursive.unwrap_or(false)
+    }
+}
+
+#[derive(FromForm, Default)]
+pub struct AggregateParams {
+    pub topic: Option<String>,
+    pub kind: Option<String>,
+    pub stat_kind: Option<String>,
+}
@@ -1,73 +1,85 @@
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
-    process::Output,
-    str,
     sync::{Arc, Mutex, RwLock},
     time::Duration,
 };
 
 use chrono::{NaiveDateTime, Utc};
-use diesel::{self, RunQueryDsl};
-use failure::Fail;
-use futures::{
-    future::{join_all, Future},
-    FutureExt,
-};
-use graphql_client::{GraphQLQuery, Response};
-use lazy_static::lazy_static;
-use quick_xml::{
-    events::{attributes::Attribute, BytesText, Event},
-    Reader,
-};
+use diesel::{self, prelude::*, sql_query, sql_types::Timestamp, sqlite::SqliteConnection};
+use futures::future::Future;
 use serde_derive::Serialize;
-use slog::{debug, error, info, o, trace, warn, Logger};
-use tokio::process::Command;
+use slog::{debug, error, info, o, Logger};
 
 use crate::{
+    backfill::{select_snapshots, BackfillInterval},
     db::Pool,
-    models::{FileKind, NewEntry},
-    schema::entries,
+    models::{Entry as DbEntry, FileKind, NewEntry, NewTask, StatKind, Task as DbTask, TaskStatus, TaskStatusMapping},
+    schema::{entries, tasks as tasks_db},
+    sources::SourceProvider,
     stats::{get_file_kind, get_file_stats, StatsResults},
-    HTTPS_CLIENT, ORGANIZATION_ROOT,
+    util::JsonValue,
+    TASK_RETENTION,
 };
 
-type DateTime = chrono::DateTime<Utc>;
-type GitObjectID = String;
-
-#[derive(GraphQLQuery)]
-#[graphql(
-    schema_path = "src/graphql/schema.json",
-    query_path = "src/graphql/packages_query.graphql",
-    response_derives = "Clone, Debug"
-)]
-pub struct PackagesQuery;
-
 #[derive(Clone, Debug, Serialize)]
 pub struct File {
     pub path: String,
     pub size: i32,
     pub revision: i32,
     pub sha: String,
-    pub last_author: String,
-    pub last_changed: NaiveDateTime,
-}
-
-pub struct FileWithoutSha {
-    pub path: String,
-    pub size: i32,
-    pub revision: i32,
+    #[serde(skip_serializing)]
+    pub blob: String,
     pub last_author: String,
     pub last_changed: NaiveDateTime,
 }
 
 #[derive(Clone, Debug, Serialize)]
 pub struct Task {
+    #[serde(skip_serializing)]
+    pub id: i32,
+
     pub created: NaiveDateTime,
+    pub branch: String,
     pub file: File,
     pub kind: FileKind,
+    pub status: TaskStatus,
+    pub started: Option<NaiveDateTime>,
 }
 type Tasks = Vec<Task>;
 
+no_arg_sql_function!(last_insert_rowid, diesel::sql_types::Integer);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Failure {
+    pub branch: String,
+    pub file: File,
+    pub kind: FileKind,
+    pub error_kind: String,
+    pub message: String,
+    pub created: NaiveDateTime,
+}
+
+impl From<DbTask> for Failure {
+    fn from(task: DbTask) -> Self {
+        Failure {
+            branch: task.branch,
+            file: File {
+                path: task.path,
+                size: task.size,
+                revision: task.revision,
+                sha: task.sha,
+                blob: task.blob,
+                last_author: task.last_author,
+                last_changed: task.last_changed,
+            },
+            kind: task.file_kind,
+            error_kind: task.error_kind.unwrap_or_default(),
+            message: task.error.unwrap_or_default(),
+            created: task.completed.unwrap_or(task.created),
+        }
+    }
+}
+
 #[derive(Clone, Serialize)]
 pub struct Actor {
     pub name: String,
@@ -89,316 +101,10 @@ pub struct Package {
     pub name: String,
     pub description: Option<String>,
     pub topics: Vec<String>,
+    pub default_branch: Option<String>,
     pub last_commit: Option<Commit>,
 }
 
-#[derive(Debug, Fail)]
-enum PackageUpdateError {
-    #[fail(display = "Missing response data key: {}", _0)]
-    MissingData(String),
-}
-
-async fn get_git_sha(logger: Logger, revision: i32, svn_path: &str) -> Option<String> {
-    let sha_future = Command::new("svn")
-        .arg("--non-interactive")
-        .arg("propget")
-        .arg("git-commit")
-        .arg("--revprop")
-        .arg("-r")
-        .arg(revision.to_string())
-        .arg(svn_path)
-        .output();
-
-    match sha_future.await {
-        Ok(Output { status, ref stdout, .. }) if status.success() => {
-            let sha = String::from_utf8_lossy(stdout).into_owned().as_str().trim().to_string();
-            Some(sha)
-        },
-        Ok(Output { stderr, .. }) => {
-            let err = String::from_utf8_lossy(&stderr);
-            error!(logger, "Error getting SHA corresponding to revision: {:?}", err; "revision" => revision);
-            None
-        },
-        Err(err) => {
-            error!(logger, "Error getting SHA corresponding to revision: {:?}", err; "revision" => revision);
-            None
-        },
-    }
-}
-
-#[allow(clippy::cognitive_complexity)]
-async fn list_files(logger: &Logger, package_name: &str, recursive: bool) -> Result<Vec<FileWithoutSha>, String> {
-    fn decode_utf8<'a>(bytes: &'a [u8], reader: &Reader<&[u8]>) -> Result<&'a str, String> {
-        str::from_utf8(bytes).map_err(|err| {
-            format!(
-                "UTF8 decoding error at position {}: {:?}",
-                reader.buffer_position(),
-                err,
-            )
-        })
-    }
-
-    fn decode_bytes(bytes: &BytesText, reader: &Reader<&[u8]>) -> Result<String, String> {
-        bytes
-            .unescape_and_decode(&reader)
-            .map_err(|err| format!("Decoding error at position {}: {:?}", reader.buffer_position(), err,))
-    }
-
-    let svn_path = format!("{}/{}/trunk", ORGANIZATION_ROOT, package_name);
-    let output = Command::new("svn")
-        .arg("--non-interactive")
-        .arg("list")
-        .arg("--xml")
-        .args(if recursive { vec!["--recursive"] } else { vec![] })
-        .arg(&svn_path)
-        .output();
-
-    match output.await {
-        Ok(Output { status, ref stdout, .. }) if status.success() => {
-            let xml = String::from_utf8_lossy(stdout);
-            let mut reader = Reader::from_str(&xml);
-            let mut buf = Vec::new();
-
-            let mut files = Vec::new();
-            let mut in_file_entry = false;
-            let (mut in_name, mut in_author, mut in_date, mut in_size) = (false, false, false, false);
-            let (mut name, mut author, mut date, mut size, mut revision) = (None, None, None, None, None);
-
-            loop {
-                match reader.read_event(&mut buf) {
-                    Ok(Event::Start(ref e)) if e.name() == b"entry" => {
-                        in_file_entry = e.attributes().any(|attr| {
-                            attr.map(|Attribute { value, key }| {
-                                decode_utf8(&key, &reader) == Ok("kind") && decode_utf8(&value, &reader) == Ok("file")
-                            })
-                            .unwrap_or(false)
-                        });
-                    },
-                    Ok(Event::Start(ref e)) if in_file_entry && e.name() == b"author" => in_author = true,
-                    Ok(Event::Start(ref e)) if in_file_entry && e.name() == b"date" => in_date = true,
-                    Ok(Event::Start(ref e)) if in_file_entry && e.name() == b"name" => in_name = true,
-                    Ok(Event::Start(ref e)) if in_file_entry && e.name() == b"size" => in_size = true,
-                    Ok(Event::Start(ref e)) if in_file_entry && e.name() == b"commit" => {
-                        for Attribute { value, key } in e.attributes().flatten() {
-                            if decode_utf8(&key, &reader)? == "revision" {
-                                revision = Some(decode_utf8(&value, &reader)?.parse::<i32>().map_err(|err| {
-                                    format!(
-                                        "Revision number parsing error at position {}: {:?}",
-                                        reader.buffer_position(),
-                                        err,
-                                    )
-                                })?);
-
-                                break;
-                            }
-                        }
-                    },
-                    Ok(Event::Text(ref e)) if in_name => {
-                        name = Some(decode_bytes(e, &reader)?);
-                    },
-                    Ok(Event::Text(ref e)) if in_date => {
-                        date = Some(
-                            NaiveDateTime::parse_from_str(
-                                &decode_bytes(e, &reader)?.to_string(),
-                                "%Y-%m-%dT%H:%M:%S.%fZ",
-                            )
-                            .map_err(|err| {
-                                format!(
-                                    "Datetime parsing error at position {}: {:?}",
-                                    reader.buffer_position(),
-                                    err,
-                                )
-                            })?,
-                        );
-                    },
-                    Ok(Event::Text(ref e)) if in_author => {
-                        author = Some(decode_bytes(e, &reader)?);
-                    },
-                    Ok(Event::Text(ref e)) if in_size => {
-                        size = Some(decode_bytes(e, &reader)?.parse::<i32>().map_err(|err| {
-                            format!(
-                                "File size number parsing error at position {}: {:?}",
-                                reader.buffer_position(),
-                                err,
-                            )
-                        })?);
-                    },
-                    Ok(Event::End(ref e)) if e.name() == b"author" => in_author = false,
-                    Ok(Event::End(ref e)) if e.name() == b"date" => in_date = false,
-                    Ok(Event::End(ref e)) if e.name() == b"name" => in_name = false,
-                    Ok(Event::End(ref e)) if e.name() == b"size" => in_size = false,
-                    Ok(Event::End(ref e)) if e.name() == b"entry" => {
-                        if in_file_entry {
-                            match (name.clone(), size, revision, author.clone(), date) {
-                                (Some(name), Some(size), Some(revision), Some(author), Some(date)) => {
-                                    trace!(
-                                        logger,
-                                        "Parsed file";
-                                        "name" => name.clone(), "size" => size, "revision" => revision, "author" => author.clone(), "date" => date.to_string(),
-                                    );
-                                    files.push(FileWithoutSha {
-                                        path: name,
-                                        size,
-                                        revision,
-                                        last_author: author,
-                                        last_changed: date,
-                                    });
-                                },
-                                _ => {
-                                    error!(
-                                        logger,
-                                        "Failed to fetch all file information";
-                                        "name" => name, "size" => size, "revision" => revision, "author" => author, "date" => date.map(|x| x.to_string()),
-                                    );
-                                },
-                            }
-                        }
-
-                        in_file_entry = false;
-                        name = None;
-                        size = None;
-                        revision = None;
-                        author = None;
-                        date = None;
-                    },
-                    Ok(Event::Eof) => break,
-                    Err(err) => panic!("Error at position {}: {:?}", reader.buffer_position(), err),
-                    _ => (),
-                }
-                buf.clear();
-            }
-
-            Ok(files)
-        },
-        Ok(Output { stderr, .. }) => {
-            let error = String::from_utf8_lossy(&stderr);
-            Err(format!("Package not found: {}", error))
-        },
-        Err(err) => Err(format!("Package search failed: {}", err)),
-    }
-}
-
-async fn get_packages(
-    logger: &Logger,
-    github_auth_token: &str,
-    github_graphql_api_endpoint: &str,
-    after: Option<&String>,
-) -> Result<(Vec<Package>, Option<String>, packages_query::PackagesQueryRateLimit), failure::Error> {
-    lazy_static! {
-        static ref TOPICS: HashSet<&'static str> = [
-            "apertium-languages",
-            "apertium-incubator",
-            "apertium-nursery",
-            "apertium-staging",
-            "apertium-trunk"
-        ]
-        .iter()
-        .cloned()
-        .collect();
-    }
-
-    debug!(logger, "Fetching repos"; "after" => after);
-
-    let query = PackagesQuery::build_query(packages_query::Variables { after: after.cloned() });
-    let response: Response<packages_query::ResponseData> = HTTPS_CLIENT
-        .post(github_graphql_api_endpoint)
-        .bearer_auth(github_auth_token)
-        .json(&query)
-        .send()
-        .await?
-        .error_for_status()?
-        .json()
-        .await?;
-
-    if let Some(errors) = response.errors {
-        for err in &errors {
-            warn!(logger, "Encountered error while fetching repos: {:?}", err; "after" => after);
-        }
-    }
-
-    let response_data: packages_query::ResponseData = response
-        .data
-        .ok_or_else(|| PackageUpdateError::MissingData("data".to_string()))?;
-
-    let repositories = response_data
-        .organization
-        .ok_or_else(|| PackageUpdateError::MissingData("data.organization".to_string()))?
-        .repositories;
-
-    let packages = repositories
-        .edges
-        .ok_or_else(|| PackageUpdateError::MissingData("data.organization.repository.edges".to_string()))?
-        .into_iter()
-        .filter_map(|repo_node| {
-            if repo_node.is_none() {
-                warn!(logger, "Missing repository node");
-            }
-
-            repo_node.and_then(|x| x.node).map(|repo| Package {
-                name: repo.name,
-                description: repo.description,
-                topics: repo.repository_topics.nodes.map_or(vec![], |topics| {
-                    topics
-                        .into_iter()
-                        .filter_map(|topic| topic.map(|x| x.topic).map(|x| x.name))
-                        .collect()
-                }),
-                last_commit: repo.ref_.and_then(|x| match x.target.on {
-                    packages_query::PackagesQueryOrganizationRepositoriesEdgesNodeRefTargetOn::Commit(commit) => commit
-                        .history
-                        .edges
-                        .and_then(|x| x.first().and_then(|y| y.clone().and_then(|z| z.node)))
-                        .and_then(|ref commit| match (&commit.author, &commit.committer) {
-                            (Some(ref author), Some(ref committer)) =>
-                                match (&author.name, &author.email, &committer.name, &committer.email) {
-                                    (Some(ref author_name), Some(ref author_email), Some(ref committer_name), Some(ref committer_email)) =>
-                                        Some(Commit {
-                                            sha: commit.oid.clone(),
-                                            message: commit.clone().message_headline,
-                                            authored: commit.authored_date.naive_utc(),
-                                            committed: commit.committed_date.naive_utc(),
-                                            author: Actor {
-                                                name: author_name.to_string(),
-                                                email: author_email.to_string(),
-                                            },
-                                            committer: Actor {
-                                                name: committer_name.to_string(),
-                                                email: committer_email.to_string(),
-                                            },
-                                        }),
-                                    _ => {
-                                        warn!(logger, "Commit author or committer missing information: author = {:?}, committer = {:?}", author, committer);
-                                        None
-                                    }
-                                }
-                            _ => {
-                                warn!(logger, "Commit missing author or committer: {:?}", commit);
-                                None
-                            },
-                        }),
-                    _ => None,
-                }),
-            })
-        })
-        .filter(|Package {topics, ..}| !TOPICS.is_disjoint(&topics.iter().map(|x| x.as_str()).collect()))
-        .collect::<Vec<_>>();
-
-    let page_info = repositories.page_info;
-    let next_after = if page_info.has_next_page {
-        page_info.end_cursor
-    } else {
-        None
-    };
-
-    let limits = response_data
-        .rate_limit
-        .ok_or_else(|| PackageUpdateError::MissingData("data.organization.rate_limit".to_string()))?;
-
-    debug!(logger, "Fetched {} packages", packages.len());
-
-    Ok((packages, next_after, limits))
-}
-
 pub struct Worker {
     pub logger: Logger,
     pub packages: RwLock<Vec<Package>>,
@@ -407,17 +113,11 @@ pub struct Worker {
     packages_update_mutex: Mutex<()>,
     pool: Pool,
     current_tasks: Arc<RwLock<HashMap<String, Tasks>>>,
-    github_graphql_api_endpoint: String,
-    github_auth_token: Option<String>,
+    pub source: Arc<dyn SourceProvider>,
 }
 
 impl Worker {
-    pub fn new(
-        pool: Pool,
-        logger: Logger,
-        github_auth_token: Option<String>,
-        github_graphql_api_endpoint: String,
-    ) -> Worker {
+    pub fn new(pool: Pool, logger: Logger, source: Arc<dyn SourceProvider>) -> Worker {
         Worker {
             pool,
             packages: RwLock::new(vec![]),
@@ -426,8 +126,7 @@ impl Worker {
             packages_update_mutex: Mutex::new(()),
             current_tasks: Arc::new(RwLock::new(HashMap::new())),
             logger,
-            github_graphql_api_endpoint,
-            github_auth_token,
+            source,
         }
     }
 
@@ -436,39 +135,70 @@ impl Worker {
         current_tasks.get(name).cloned()
     }
 
+    fn get_package_default_branch(&self, name: &str) -> Option<String> {
+        let packages = self.packages.read().unwrap();
+        packages
+            .iter()
+            .find(|package| package.name == name)
+            .and_then(|package| package.default_branch.clone())
+    }
+
+    pub async fn get_branch(&self, name: &str, maybe_branch: Option<&str>) -> Result<String, String> {
+        if let Some(branch) = maybe_branch {
+            return Ok(branch.to_string());
+        }
+
+        match self.get_package_default_branch(name) {
+            Some(default_branch) => Ok(default_branch),
+            None => self.source.get_default_branch(&self.logger, name).await,
+        }
+    }
+
     pub async fn build_tasks(
         &self,
         name: &str,
         maybe_kind: Option<&FileKind>,
+        maybe_branch: Option<&str>,
         recursive: bool,
-    ) -> Result<(Tasks, Tasks, Vec<impl Future<Output = (Task, StatsResults)>>), String> {
+        force: bool,
+    ) -> Result<
+        (
+            Tasks,
+            Tasks,
+            Vec<NewEntry>,
+            Vec<impl Future<Output = (Task, StatsResults)>>,
+        ),
+        String,
+    > {
+        let branch = self.get_branch(name, maybe_branch).await?;
         let logger = self.logger.new(o!(
             "package" => name.to_string(),
+            "branch" => branch.clone(),
             "recursive" => recursive,
         ));
 
-        let files_without_shas = list_files(&logger, name, recursive).await?;
+        let revision = format!("refs/heads/{}", branch);
+        let files = self.source.list_files(&logger, name, &revision, recursive).await?;
 
         let mut current_tasks = self.current_tasks.write().unwrap();
         let current_package_tasks = current_tasks.entry(name.to_string());
 
-        let requested_files = files_without_shas
+        let new_tasks = files
             .into_iter()
             .filter_map(|file| {
                 get_file_kind(&file.path).and_then(|file_kind| {
                     let requested_kind = maybe_kind.map_or(true, |kind| kind == &file_kind);
-                    let in_progress = match current_package_tasks {
-                        Entry::Occupied(ref occupied) => occupied.get().iter().any(
-                            |Task {
-                                 kind,
-                                 file: File { path, .. },
-                                 ..
-                             }| { kind == &file_kind && path == &file.path },
-                        ),
-                        _ => false,
-                    };
+                    let in_progress = Worker::is_in_progress(&current_package_tasks, &branch, &file, &file_kind);
                     if requested_kind && !in_progress {
-                        Some((file_kind, file))
+                        Some(Task {
+                            id: 0,
+                            kind: file_kind,
+                            branch: branch.clone(),
+                            file,
+                            created: Utc::now().naive_utc(),
+                            status: TaskStatus::Queued,
+                            started: None,
+                        })
                     } else {
                         None
                     }
@@ -476,83 +206,441 @@ impl Worker {
             })
             .collect::<Vec<_>>();
 
-        let svn_path = format!("{}/{}/trunk", ORGANIZATION_ROOT, name);
-        let mut unique_revisions = requested_files
+        let (mut new_tasks, reused_entries) = if force {
+            (new_tasks, vec![])
+        } else {
+            self.reuse_unchanged_entries(&logger, name, new_tasks)?
+        };
+
+        self.persist_new_tasks(&logger, name, &mut new_tasks)?;
+        let (new_tasks, in_progress_tasks) = Worker::record_new_tasks(current_package_tasks, new_tasks)?;
+
+        let futures = new_tasks
             .iter()
-            .map(|(_, FileWithoutSha { revision, .. })| *revision)
+            .map(|task| self.make_task_future(&logger, name, task))
+            .collect();
+
+        Ok((new_tasks, in_progress_tasks, reused_entries, futures))
+    }
+
+    pub async fn list_backfill_snapshots(
+        &self,
+        name: &str,
+        maybe_branch: Option<&str>,
+        interval: &BackfillInterval,
+    ) -> Result<(String, Vec<Commit>), String> {
+        let branch = self.get_branch(name, maybe_branch).await?;
+        let logger = self.logger.new(o!(
+            "package" => name.to_string(),
+            "branch" => branch.clone(),
+        ));
+
+        let commits = self
+            .source
+            .list_commits(&logger, name, &format!("refs/heads/{}", branch))
+            .await?;
+        let snapshots = select_snapshots(&commits, interval)
+            .into_iter()
+            .cloned()
             .collect::<Vec<_>>();
-        unique_revisions.sort_unstable();
-        unique_revisions.dedup();
-        debug!(logger, "Found {} unique revisions", unique_revisions.len());
-
-        let sha_futures = join_all(
-            unique_revisions
-                .iter()
-                .map(|&revision| get_git_sha(logger.clone(), revision, &svn_path))
-                .collect::<Vec<_>>(),
-        );
-        let revision_sha_mapping = unique_revisions
+        info!(logger, "Backfilling package"; "commits" => commits.len(), "snapshots" => snapshots.len());
+
+        Ok((branch, snapshots))
+    }
+
+    pub async fn build_backfill_tasks(
+        &self,
+        name: &str,
+        branch: &str,
+        snapshots: &[Commit],
+        recursive: bool,
+    ) -> Result<(Tasks, Vec<impl Future<Output = (Task, StatsResults)>>), String> {
+        let logger = self.logger.new(o!(
+            "package" => name.to_string(),
+            "branch" => branch.to_string(),
+            "recursive" => recursive,
+        ));
+
+        // Files unchanged between snapshots share a SHA and only need to be computed once
+        let mut seen_files = HashSet::new();
+        let mut files = Vec::new();
+        for commit in snapshots {
+            for file in self
+                .source
+                .list_fetched_files(&logger, name, &commit.sha, recursive)
+                .await?
+            {
+                if let Some(file_kind) = get_file_kind(&file.path) {
+                    if seen_files.insert((file.path.clone(), file.sha.clone())) {
+                        files.push((file, file_kind));
+                    }
+                }
+            }
+        }
+
+        let (new_tasks, _, futures) = self.build_file_tasks(&logger, name, branch, files)?;
+        Ok((new_tasks, futures))
+    }
+
+    pub async fn list_revision_files(
+        &self,
+        logger: &Logger,
+        name: &str,
+        revision: &str,
+        maybe_kind: Option<&FileKind>,
+        recursive: bool,
+    ) -> Result<Vec<(File, FileKind)>, String> {
+        let files = self.source.list_files(logger, name, revision, recursive).await?;
+        Ok(files
             .into_iter()
-            .zip(sha_futures.await)
-            .collect::<HashMap<i32, Option<String>>>();
-        debug!(
-            logger,
-            "Fetched Git SHAs for {} unique revisions",
-            revision_sha_mapping.len()
-        );
+            .filter_map(|file| get_file_kind(&file.path).map(|file_kind| (file, file_kind)))
+            .filter(|(_, file_kind)| maybe_kind.map_or(true, |kind| kind == file_kind))
+            .collect())
+    }
+
+    pub fn build_file_tasks(
+        &self,
+        logger: &Logger,
+        name: &str,
+        branch: &str,
+        files: Vec<(File, FileKind)>,
+    ) -> Result<(Tasks, Tasks, Vec<impl Future<Output = (Task, StatsResults)>>), String> {
+        let mut current_tasks = self.current_tasks.write().unwrap();
+        let current_package_tasks = current_tasks.entry(name.to_string());
 
-        let new_tasks = requested_files
+        let new_tasks = files
             .into_iter()
-            .filter_map(
-                |(
-                    file_kind,
-                    FileWithoutSha {
-                        path,
-                        size,
-                        revision,
-                        last_author,
-                        last_changed,
-                    },
-                )| match revision_sha_mapping.get(&revision) {
-                    Some(Some(sha)) => Some(Task {
-                        kind: file_kind,
-                        file: File {
-                            path,
-                            size,
-                            revision,
-                            last_author,
-                            last_changed,
-                            sha: sha.to_string(),
-                        },
-                        created: Utc::now().naive_utc(),
-                    }),
-                    _ => {
-                        error!(logger, "Missing SHA corresponding to file"; "path" => path, "revision" => revision);
-                        None
-                    },
-                },
-            )
+            .filter(|(file, file_kind)| !Worker::is_in_progress(&current_package_tasks, branch, file, file_kind))
+            .map(|(file, file_kind)| Task {
+                id: 0,
+                kind: file_kind,
+                branch: branch.to_string(),
+                file,
+                created: Utc::now().naive_utc(),
+                status: TaskStatus::Queued,
+                started: None,
+            })
             .collect::<Vec<_>>();
 
+        let mut new_tasks = self.filter_uncomputed_tasks(logger, name, new_tasks)?;
+        self.persist_new_tasks(logger, name, &mut new_tasks)?;
         let (new_tasks, in_progress_tasks) = Worker::record_new_tasks(current_package_tasks, new_tasks)?;
 
         let futures = new_tasks
             .iter()
-            .map(|task| {
-                let task = task.clone();
-                let logger = logger.new(o!(
-                    "path" => task.file.path.clone(),
-                    "kind" => task.kind.to_string(),
-                ));
-                get_file_stats(logger, task.file.path.clone(), name.to_string(), task.kind.clone())
-                    .map(move |stats| (task, stats))
-            })
+            .map(|task| self.make_task_future(logger, name, task))
             .collect();
 
         Ok((new_tasks, in_progress_tasks, futures))
     }
 
-    pub fn handle_task_completion(&self, name: &str, result: &(Task, StatsResults)) -> Vec<NewEntry> {
+    pub fn resume_tasks(&self) -> Result<Vec<(String, Vec<impl Future<Output = (Task, StatsResults)>>)>, String> {
+        let conn = self.pool.get().map_err(|err| {
+            error!(self.logger, "Error getting database connection: {:?}", err);
+            "Unable to resume tasks".to_string()
+        })?;
+
+        let unfinished_statuses = vec![TaskStatus::Queued, TaskStatus::Running];
+        let retention = chrono::Duration::from_std(TASK_RETENTION).unwrap_or_else(|_| chrono::Duration::zero());
+        let db_tasks = conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                // Completed tasks are pruned once they age out, except for each file's latest failure
+                sql_query(
+                    "
+                        DELETE FROM tasks
+                        WHERE completed < ? AND id NOT IN (
+                            SELECT id FROM (
+                                SELECT id, status, MAX(completed)
+                                FROM tasks
+                                WHERE completed IS NOT NULL
+                                GROUP BY name, branch, file_kind, path
+                            )
+                            WHERE status = ?
+                        )
+                    ",
+                )
+                .bind::<Timestamp, _>(Utc::now().naive_utc() - retention)
+                .bind::<TaskStatusMapping, _>(TaskStatus::Failed)
+                .execute(&*conn)?;
+
+                // Tasks that were running when the service stopped need to be started over
+                diesel::update(tasks_db::table.filter(tasks_db::status.eq(TaskStatus::Running)))
+                    .set((
+                        tasks_db::status.eq(TaskStatus::Queued),
+                        tasks_db::started.eq(None as Option<NaiveDateTime>),
+                    ))
+                    .execute(&*conn)?;
+                tasks_db::table
+                    .filter(tasks_db::status.eq_any(unfinished_statuses))
+                    .order(tasks_db::created)
+                    .load::<DbTask>(&*conn)
+            })
+            .map_err(|err| {
+                error!(self.logger, "Error fetching unfinished tasks: {:?}", err);
+                "Unable to resume tasks".to_string()
+            })?;
+
+        let mut resumed_tasks: HashMap<String, Tasks> = HashMap::new();
+        for db_task in db_tasks {
+            resumed_tasks.entry(db_task.name.clone()).or_default().push(Task {
+                id: db_task.id,
+                created: db_task.created,
+                branch: db_task.branch,
+                file: File {
+                    path: db_task.path,
+                    size: db_task.size,
+                    revision: db_task.revision,
+                    sha: db_task.sha,
+                    blob: db_task.blob,
+                    last_author: db_task.last_author,
+                    last_changed: db_task.last_changed,
+                },
+                kind: db_task.file_kind,
+                status: TaskStatus::Queued,
+                started: None,
+            });
+        }
+
+        let mut current_tasks = self.current_tasks.write().unwrap();
+        Ok(resumed_tasks
+            .into_iter()
+            .map(|(name, tasks)| {
+                let logger = self.logger.new(o!("package" => name.clone()));
+                info!(logger, "Resuming unfinished tasks"; "count" => tasks.len());
+                let futures = tasks
+                    .iter()
+                    .map(|task| self.make_task_future(&logger, &name, task))
+                    .collect();
+                current_tasks.entry(name.clone()).or_default().extend(tasks);
+                (name, futures)
+            })
+            .collect())
+    }
+
+    fn make_task_future(&self, logger: &Logger, name: &str, task: &Task) -> impl Future<Output = (Task, StatsResults)> {
+        let task = task.clone();
+        let logger = logger.new(o!(
+            "path" => task.file.path.clone(),
+            "kind" => task.kind.to_string(),
+        ));
+        let source = self.source.clone();
+        let pool = self.pool.clone();
+        let current_tasks_guard = self.current_tasks.clone();
+        let name = name.to_string();
+        async move {
+            Worker::record_task_start(&logger, &pool, &current_tasks_guard, &name, &task);
+            let stats = match source.fetch_file(&logger, &name, &task.file).await {
+                Ok(body) => get_file_stats(&logger, &body, &task.file.path, &task.kind),
+                Err(err) => Err(err),
+            };
+            (task, stats)
+        }
+    }
+
+    pub async fn compute_file_stats(
+        &self,
+        logger: &Logger,
+        name: &str,
+        file: &File,
+        file_kind: &FileKind,
+    ) -> StatsResults {
+        let body = self.source.fetch_file(logger, name, file).await?;
+        get_file_stats(logger, &body, &file.path, file_kind)
+    }
+
+    fn persist_new_tasks(&self, logger: &Logger, name: &str, tasks: &mut [Task]) -> Result<(), String> {
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.pool.get().map_err(|err| {
+            error!(logger, "Error getting database connection: {:?}", err);
+            "Unable to persist tasks".to_string()
+        })?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            for task in tasks.iter_mut() {
+                diesel::insert_into(tasks_db::table)
+                    .values(&NewTask {
+                        name: name.to_string(),
+                        branch: task.branch.clone(),
+                        path: task.file.path.clone(),
+                        file_kind: task.kind.clone(),
+                        revision: task.file.revision,
+                        sha: task.file.sha.clone(),
+                        blob: task.file.blob.clone(),
+                        size: task.file.size,
+                        last_author: task.file.last_author.clone(),
+                        last_changed: task.file.last_changed,
+                        status: task.status.clone(),
+                        created: task.created,
+                    })
+                    .execute(&*conn)?;
+                task.id = diesel::select(last_insert_rowid).get_result(&*conn)?;
+            }
+            Ok(())
+        })
+        .map_err(|err| {
+            error!(logger, "Error persisting tasks: {:?}", err);
+            "Unable to persist tasks".to_string()
+        })
+    }
+
+    fn record_task_start(
+        logger: &Logger,
+        pool: &Pool,
+        current_tasks_guard: &RwLock<HashMap<String, Tasks>>,
+        name: &str,
+        task: &Task,
+    ) {
+        let started = Utc::now().naive_utc();
+
+        {
+            let mut current_tasks = current_tasks_guard.write().unwrap();
+            if let Some(current_task) = current_tasks
+                .get_mut(name)
+                .and_then(|tasks| tasks.iter_mut().find(|current_task| current_task.id == task.id))
+            {
+                current_task.status = TaskStatus::Running;
+                current_task.started = Some(started);
+            }
+        }
+
+        let result = pool.get().map_err(|err| format!("{:?}", err)).and_then(|conn| {
+            diesel::update(tasks_db::table.find(task.id))
+                .set((tasks_db::status.eq(TaskStatus::Running), tasks_db::started.eq(started)))
+                .execute(&*conn)
+                .map_err(|err| format!("{:?}", err))
+        });
+        if let Err(err) = result {
+            error!(logger, "Error recording task start: {}", err);
+        }
+    }
+
+    fn is_in_progress(
+        current_package_tasks: &Entry<String, Tasks>,
+        branch: &str,
+        file: &File,
+        file_kind: &FileKind,
+    ) -> bool {
+        match current_package_tasks {
+            Entry::Occupied(occupied) => occupied.get().iter().any(|task| {
+                &task.kind == file_kind
+                    && task.branch == branch
+                    && task.file.path == file.path
+                    && task.file.sha == file.sha
+            }),
+            Entry::Vacant(_) => false,
+        }
+    }
+
+    fn filter_uncomputed_tasks(&self, logger: &Logger, name: &str, tasks: Tasks) -> Result<Tasks, String> {
+        let conn = self.pool.get().map_err(|err| {
+            error!(logger, "Error getting database connection: {:?}", err);
+            "Unable to check existing entries".to_string()
+        })?;
+
+        let mut uncomputed_tasks = Vec::new();
+        for task in tasks {
+            let existing_entries =
+                Worker::get_existing_entries(&conn, name, &task.file, &task.kind).map_err(|err| {
+                    error!(logger, "Error fetching existing entries: {:?}", err);
+                    "Unable to check existing entries".to_string()
+                })?;
+
+            if existing_entries.is_empty() {
+                uncomputed_tasks.push(task);
+            }
+        }
+
+        Ok(uncomputed_tasks)
+    }
+
+    fn reuse_unchanged_entries(
+        &self,
+        logger: &Logger,
+        name: &str,
+        tasks: Tasks,
+    ) -> Result<(Tasks, Vec<NewEntry>), String> {
+        let conn = self.pool.get().map_err(|err| {
+            error!(logger, "Error getting database connection: {:?}", err);
+            "Unable to reuse existing entries".to_string()
+        })?;
+
+        let mut new_tasks = Vec::new();
+        let mut reused_entries = Vec::new();
+        for task in tasks {
+            let existing_entries =
+                Worker::get_existing_entries(&conn, name, &task.file, &task.kind).map_err(|err| {
+                    error!(logger, "Error fetching existing entries: {:?}", err);
+                    "Unable to reuse existing entries".to_string()
+                })?;
+
+            if existing_entries.is_empty() {
+                new_tasks.push(task);
+            } else {
+                debug!(logger, "Reusing entries for unchanged file"; "path" => task.file.path.clone(), "sha" => task.file.sha.clone());
+                reused_entries.extend(
+                    existing_entries
+                        .into_iter()
+                        .map(|DbEntry { stat_kind, value, .. }| Worker::make_entry(name, &task, stat_kind, value)),
+                );
+            }
+        }
+
+        if !reused_entries.is_empty() {
+            diesel::insert_into(entries::table)
+                .values(&reused_entries)
+                .execute(&*conn)
+                .map_err(|err| {
+                    error!(logger, "Error persisting reused entries: {:?}", err);
+                    "Unable to reuse existing entries".to_string()
+                })?;
+        }
+
+        Ok((new_tasks, reused_entries))
+    }
+
+    pub fn get_existing_entries(
+        conn: &SqliteConnection,
+        name: &str,
+        file: &File,
+        file_kind: &FileKind,
+    ) -> QueryResult<Vec<DbEntry>> {
+        let existing_entries = entries::table
+            .filter(entries::name.eq(name))
+            .filter(entries::path.eq(&file.path))
+            .filter(entries::sha.eq(&file.sha))
+            .filter(entries::file_kind.eq(file_kind))
+            .order(entries::requested.desc())
+            .load::<DbEntry>(conn)?;
+
+        let latest_requested = existing_entries.first().map(|entry| entry.requested);
+        Ok(existing_entries
+            .into_iter()
+            .take_while(|entry| Some(entry.requested) == latest_requested)
+            .collect())
+    }
+
+    fn make_entry(name: &str, task: &Task, stat_kind: StatKind, value: JsonValue) -> NewEntry {
+        NewEntry {
+            name: name.to_string(),
+            created: Utc::now().naive_utc(),
+            requested: task.created,
+            path: task.file.path.clone(),
+            stat_kind,
+            file_kind: task.kind.clone(),
+            value,
+            revision: task.file.revision,
+            sha: task.file.sha.clone(),
+            size: task.file.size,
+            last_author: task.file.last_author.clone(),
+            last_changed: task.file.last_changed,
+            branch: task.branch.clone(),
+        }
+    }
+
+    pub fn handle_task_completion(&self, name: &str, result: &(Task, StatsResults)) -> Result<Vec<NewEntry>, Failure> {
         let current_tasks_guard = self.current_tasks.clone();
         let pool = self.pool.clone();
 
@@ -568,89 +656,85 @@ impl Worker {
             Worker::record_task_completion(current_tasks.entry(name.to_string()), &task);
         }
 
-        match maybe_stats {
+        let completed = Utc::now().naive_utc();
+        let outcome = match maybe_stats {
             Ok(stats) => {
                 debug!(logger, "Completed executing task");
 
-                let new_entries = stats
+                Ok(stats
                     .iter()
-                    .map(|(kind, value)| NewEntry {
-                        name: name.to_string(),
-                        created: Utc::now().naive_utc(),
-                        requested: task.created,
-                        path: task.file.path.clone(),
-                        stat_kind: kind.clone(),
-                        file_kind: task.kind.clone(),
-                        value: value.clone().into(),
-                        revision: task.file.revision,
-                        sha: task.file.sha.clone(),
-                        size: task.file.size,
-                        last_author: task.file.last_author.clone(),
-                        last_changed: task.file.last_changed,
-                    })
-                    .collect::<Vec<_>>();
-
-                match pool.get() {
-                    Ok(conn) => {
-                        diesel::insert_into(entries::table)
-                            .values(&new_entries)
-                            .execute(&*conn)
-                            .unwrap();
-                        new_entries
-                    },
-                    Err(err) => {
-                        error!(logger, "Error persisting task results: {:?}", err);
-                        new_entries
-                    },
-                }
+                    .map(|(kind, value)| Worker::make_entry(name, task, kind.clone(), value.clone().into()))
+                    .collect::<Vec<_>>())
             },
             Err(err) => {
                 error!(logger, "Error executing task: {:?}", err);
-                vec![]
+
+                Err(Failure {
+                    branch: task.branch.clone(),
+                    file: task.file.clone(),
+                    kind: task.kind.clone(),
+                    error_kind: err.kind().to_string(),
+                    message: err.to_string(),
+                    created: completed,
+                })
+            },
+        };
+
+        match pool.get() {
+            Ok(conn) => {
+                let result = conn.transaction::<_, diesel::result::Error, _>(|| {
+                    let (status, error_kind, error) = match outcome {
+                        Ok(ref new_entries) => {
+                            if !new_entries.is_empty() {
+                                diesel::insert_into(entries::table)
+                                    .values(new_entries)
+                                    .execute(&*conn)?;
+                            }
+                            (TaskStatus::Succeeded, None, None)
+                        },
+                        Err(Failure {
+                            ref error_kind,
+                            ref message,
+                            ..
+                        }) => (TaskStatus::Failed, Some(error_kind.clone()), Some(message.clone())),
+                    };
+                    diesel::update(tasks_db::table.find(task.id))
+                        .set((
+                            tasks_db::status.eq(status),
+                            tasks_db::error_kind.eq(error_kind),
+                            tasks_db::error.eq(error),
+                            tasks_db::completed.eq(completed),
+                        ))
+                        .execute(&*conn)?;
+                    Ok(())
+                });
+                if let Err(err) = result {
+                    error!(logger, "Error persisting task results: {:?}", err);
+                }
+            },
+            Err(err) => {
+                error!(logger, "Error persisting task results: {:?}", err);
             },
         }
+
+        outcome
     }
 
     pub async fn update_packages(&self) -> Result<Duration, failure::Error> {
         let _guard = self.packages_update_mutex.lock().unwrap();
-        let github_auth_token = self
-            .github_auth_token
-            .as_ref()
-            .expect("package list update requires a GitHub auth token")
-            .as_str();
-        let mut packages = Vec::new();
-
-        let (mut new_packages, mut after, mut rate_limits) =
-            get_packages(&self.logger, github_auth_token, &self.github_graphql_api_endpoint, None).await?;
-        let mut total_cost = rate_limits.cost;
-        packages.append(&mut new_packages);
-        while after.is_some() {
-            let (mut new_packages, new_after, new_rate_limits) = get_packages(
-                &self.logger,
-                github_auth_token,
-                &self.github_graphql_api_endpoint,
-                after.as_ref(),
-            )
-            .await?;
-            after = new_after;
-            rate_limits = new_rate_limits;
-            total_cost += rate_limits.cost;
-            packages.append(&mut new_packages);
-        }
+        let (mut packages, next_update) = self.source.list_packages(&self.logger).await?;
 
         let mut packages_lock = self.packages.write().unwrap();
         packages_lock.clear();
         packages_lock.append(&mut packages);
         *self.packages_updated.write().unwrap() = Some(Utc::now().naive_utc());
 
-        let next_update = (rate_limits.reset_at - Utc::now()) / ((rate_limits.remaining / total_cost) as i32);
         info!(
             self.logger,
             "Completed package list update";
-            "length" => packages_lock.len(), "total_cost" => total_cost,
-            "cost_remaining" => rate_limits.remaining, "next_update_min" => next_update.to_string()
+            "length" => packages_lock.len(), "next_update" => format!("{:?}", next_update)
         );
-        Ok(chrono::Duration::to_std(&next_update)?)
+        Ok(next_update)
     }
 
     pub fn record_next_packages_update(&self, next_update: Duration) {
@@ -663,11 +747,7 @@ impl Worker {
 
     fn record_task_completion(current_package_tasks: Entry<String, Tasks>, task: &Task) {
         if let Entry::Occupied(mut occupied) = current_package_tasks {
-            if let Some(position) = occupied
-                .get()
-                .iter()
-                .position(|&Task { ref kind, ref file, .. }| kind == &task.kind && file.path == task.file.path)
-            {
+            if let Some(position) = occupied.get().iter().position(|Task { id, .. }| id == &task.id) {
                 occupied.get_mut().remove(position);
                 if occupied.get().is_empty() {
                     occupied.remove_entry();